@@ -1,8 +1,11 @@
 // rust std imports
 use std::borrow::Cow;
+use std::fmt::{self, Debug};
 use std::io::{self, Read};
+use std::ops::Range;
 
 // external imports
+use bstr::ByteSlice;
 use bytemuck::{cast_slice_mut, zeroed_vec, Pod};
 use encoding_rs::{Encoding, WINDOWS_1252};
 use memchr::memchr;
@@ -16,6 +19,9 @@ pub struct Reader<'a> {
     pub cursor: io::Cursor<&'a [u8]>,
     #[default(WINDOWS_1252)]
     pub encoding: &'static Encoding,
+    /// When set, [`Reader::unexpected_tag`] skips over unexpected subrecords and collects their
+    /// byte ranges here, rather than failing.
+    pub skipped_subrecords: Option<Vec<Range<u64>>>,
 }
 
 impl<'a> Reader<'a> {
@@ -106,7 +112,7 @@ impl<'a> Reader<'a> {
 
     pub fn expect<L>(&mut self, expected: L) -> io::Result<()>
     where
-        L: Copy + Load + PartialEq,
+        L: Copy + Debug + Load + PartialEq,
     {
         let pos = self.cursor.position();
         let value: L = self.load()?;
@@ -114,10 +120,35 @@ impl<'a> Reader<'a> {
             Ok(())
         } else {
             self.cursor.set_position(pos);
-            Self::error("Unexpected Value")
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                UnexpectedValue {
+                    position: pos,
+                    expected: format!("{expected:?}"),
+                    actual: format!("{value:?}"),
+                },
+            ))
         }
     }
 
+    /// Handle a subrecord `tag` that the `record` being loaded does not know, which must have just
+    /// been loaded.
+    ///
+    /// Fails with an [`UnexpectedTag`] error, unless `skipped_subrecords` is set in which case the
+    /// subrecord is skipped over and its byte range collected.
+    pub fn unexpected_tag(&mut self, record: &'static str, tag: [u8; 4]) -> io::Result<()> {
+        if self.skipped_subrecords.is_none() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, UnexpectedTag { record, tag }));
+        }
+        let start = self.cursor.position() - 4;
+        let len: u32 = self.load()?;
+        let end = self.skip(len)?;
+        if let Some(skipped) = &mut self.skipped_subrecords {
+            skipped.push(start..end);
+        }
+        Ok(())
+    }
+
     pub fn skip(&mut self, len: u32) -> io::Result<u64> {
         let old_pos = self.cursor.position();
         let new_pos = old_pos + u64::from(len);
@@ -130,6 +161,40 @@ impl<'a> Reader<'a> {
     }
 }
 
+/// The error payload produced by [`Reader::expect`] on mismatched values.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnexpectedValue {
+    /// The position of the mismatched value within the reader.
+    pub position: u64,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for UnexpectedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unexpected Value: expected {}, found {}", self.expected, self.actual)
+    }
+}
+
+impl std::error::Error for UnexpectedValue {}
+
+/// The error payload produced by [`Reader::unexpected_tag`] on unknown subrecords.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnexpectedTag {
+    /// The tag of the record being loaded.
+    pub record: &'static str,
+    /// The tag of the unknown subrecord.
+    pub tag: [u8; 4],
+}
+
+impl fmt::Display for UnexpectedTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unexpected Tag: {}::{}", self.record, self.tag.to_str_lossy())
+    }
+}
+
+impl std::error::Error for UnexpectedTag {}
+
 impl Read for Reader<'_> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         self.cursor.read(buf)
@@ -96,3 +96,26 @@ pub mod cell_references {
         Ok(hashmap)
     }
 }
+
+pub mod moved_references {
+    use super::*;
+
+    type T = HashMap<(u32, u32), (i32, i32)>;
+
+    pub fn serialize<S>(data: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut moved_references: Vec<_> = data.iter().collect();
+        moved_references.sort_unstable();
+        serializer.collect_seq(moved_references)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let moved_references: Vec<((u32, u32), (i32, i32))> = serde::Deserialize::deserialize(deserializer)?;
+        Ok(moved_references.into_iter().collect())
+    }
+}
@@ -6,6 +6,8 @@ pub use types::*;
 pub mod traits;
 pub use traits::*;
 
+pub use encoding_rs::{Encoding, WINDOWS_1250, WINDOWS_1251, WINDOWS_1252};
+
 pub(crate) mod features;
 pub(crate) mod macros;
 
@@ -25,6 +25,7 @@ macro_rules! make_delegate {
         ///    match object {
         ///       inner => {
         ///          // code here must be valid for all `TES3Object` variants
+        ///          // including `UnknownRecord`
         ///       }
         ///    }
         /// }
@@ -43,6 +44,7 @@ macro_rules! make_delegate {
                     $(
                         TES3Object::$T($D name) => $D body,
                     )*
+                    TES3Object::Unknown($D name) => $D body,
                 }
             }
         }
@@ -1,6 +1,11 @@
+mod diff;
+pub use diff::*;
+
 mod editor_id;
 pub use editor_id::*;
 
+mod masters;
+
 mod sort_objects;
 
 mod type_info;
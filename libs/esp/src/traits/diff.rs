@@ -0,0 +1,117 @@
+// rust std imports
+use std::fmt::Debug;
+use std::hash::Hash;
+
+// internal imports
+use crate::prelude::*;
+
+/// Field level comparison of two values.
+///
+/// Implemented for all record types by `#[esp_meta]`. See [`diff`] for comparing entire plugins.
+pub trait Diff {
+    /// Append the differences between `self` and `other` to `changes`.
+    ///
+    /// The `path` is the path of `self`, and is extended with the field names and indices of any
+    /// nested values (e.g. `Npc.data.level` or `Cell.references[(0, 1234)].translation[2]`).
+    fn diff(&self, other: &Self, path: &mut String, changes: &mut Vec<FieldChange>);
+}
+
+macro_rules! impl_diff_value {
+    ($($type:ty),*) => {
+        $(
+            impl Diff for $type {
+                #[inline]
+                fn diff(&self, other: &Self, path: &mut String, changes: &mut Vec<FieldChange>) {
+                    FieldChange::compare(self, other, path, changes);
+                }
+            }
+        )*
+    };
+}
+
+impl_diff_value!(bool, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64, String);
+
+impl<A, B> Diff for (A, B)
+where
+    A: Debug + PartialEq,
+    B: Debug + PartialEq,
+{
+    fn diff(&self, other: &Self, path: &mut String, changes: &mut Vec<FieldChange>) {
+        FieldChange::compare(self, other, path, changes);
+    }
+}
+
+impl<A, B, C> Diff for (A, B, C)
+where
+    A: Debug + PartialEq,
+    B: Debug + PartialEq,
+    C: Debug + PartialEq,
+{
+    fn diff(&self, other: &Self, path: &mut String, changes: &mut Vec<FieldChange>) {
+        FieldChange::compare(self, other, path, changes);
+    }
+}
+
+impl<T: Diff + ?Sized> Diff for Box<T> {
+    fn diff(&self, other: &Self, path: &mut String, changes: &mut Vec<FieldChange>) {
+        (**self).diff(other, path, changes);
+    }
+}
+
+impl<T: Diff + Debug> Diff for Option<T> {
+    fn diff(&self, other: &Self, path: &mut String, changes: &mut Vec<FieldChange>) {
+        match (self, other) {
+            (Some(this), Some(other)) => this.diff(other, path, changes),
+            (None, None) => {}
+            _ => changes.push(FieldChange::new(path, self.as_ref(), other.as_ref())),
+        }
+    }
+}
+
+impl<T: Diff, const N: usize> Diff for [T; N] {
+    fn diff(&self, other: &Self, path: &mut String, changes: &mut Vec<FieldChange>) {
+        for (i, (this, other)) in self.iter().zip(other).enumerate() {
+            with_index(path, i, |path| this.diff(other, path, changes));
+        }
+    }
+}
+
+impl<T: Diff + Debug> Diff for Vec<T> {
+    fn diff(&self, other: &Self, path: &mut String, changes: &mut Vec<FieldChange>) {
+        for i in 0..self.len().max(other.len()) {
+            with_index(path, i, |path| match (self.get(i), other.get(i)) {
+                (Some(this), Some(other)) => this.diff(other, path, changes),
+                (this, other) => changes.push(FieldChange::new(path, this, other)),
+            });
+        }
+    }
+}
+
+impl<K, V> Diff for HashMap<K, V>
+where
+    K: Debug + Eq + Hash + Ord,
+    V: Diff + Debug,
+{
+    fn diff(&self, other: &Self, path: &mut String, changes: &mut Vec<FieldChange>) {
+        let mut keys: Vec<_> = self
+            .keys()
+            .chain(other.keys().filter(|key| !self.contains_key(*key)))
+            .collect();
+        keys.sort_unstable();
+        for key in keys {
+            with_index(path, key, |path| match (self.get(key), other.get(key)) {
+                (Some(this), Some(other)) => this.diff(other, path, changes),
+                (this, other) => changes.push(FieldChange::new(path, this, other)),
+            });
+        }
+    }
+}
+
+/// Call `f` with `path` temporarily extended by `[index]`.
+fn with_index(path: &mut String, index: impl Debug, f: impl FnOnce(&mut String)) {
+    use std::fmt::Write;
+    let len = path.len();
+    let _ = write!(path, "[{index:?}]");
+    f(path);
+    path.truncate(len);
+}
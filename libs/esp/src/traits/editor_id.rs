@@ -70,6 +70,20 @@ impl EditorId for PathGrid {
     }
 }
 
+impl EditorId for UnknownRecord {
+    fn editor_id(&self) -> Cow<'_, str> {
+        // Most records store their id in a leading `NAME` subrecord.
+        let mut stream = Reader::new(&self.bytes);
+        stream.encoding = self.encoding;
+        if stream.expect(*b"NAME").is_ok() {
+            if let Ok(id) = stream.load::<String>() {
+                return id.into();
+            }
+        }
+        "".into()
+    }
+}
+
 fn with_grid(name: &str, grid: (i32, i32)) -> String {
     let mut buffer = itoa::Buffer::new();
     let x = buffer.format(grid.0);
@@ -0,0 +1,202 @@
+// rust std imports
+use std::path::Path;
+
+// internal imports
+use crate::prelude::*;
+
+impl Plugin {
+    /// The masters of this plugin, as file names and file sizes.
+    pub fn masters(&self) -> &[(String, u64)] {
+        self.header().map_or(&[], |header| &header.masters)
+    }
+
+    /// Replace the masters of this plugin.
+    ///
+    /// The `mast_index` of all references (including moved references) are rewritten to match the
+    /// new positions of their masters. Masters are matched by file name, ignoring case.
+    ///
+    /// Returns an error if any references belong to a master that is not in the new list, in which
+    /// case the plugin is left unchanged.
+    pub fn set_masters(&mut self, masters: Vec<(String, u64)>) -> io::Result<()> {
+        if masters.len() > 0xFF {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Too many masters (limit: 255)"));
+        }
+
+        // Maps previous master indices to new master indices. Index 0 refers to the plugin itself.
+        let remap: Vec<Option<u32>> = std::iter::once(Some(0))
+            .chain(self.masters().iter().map(|(name, _)| {
+                let index = masters.iter().position(|(other, _)| other.eq_ignore_ascii_case(name))?;
+                u32::try_from(index + 1).ok()
+            }))
+            .collect();
+
+        for cell in self.objects_of_type::<Cell>() {
+            for &(mast_index, refr_index) in cell.references.keys().chain(cell.moved_references.keys()) {
+                match remap.get(mast_index as usize) {
+                    Some(Some(_)) => {}
+                    Some(None) => {
+                        let (name, _) = &self.masters()[mast_index as usize - 1];
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!(
+                                "Master '{name}' is still referenced by {} ({mast_index}, {refr_index})",
+                                cell.editor_id()
+                            ),
+                        ));
+                    }
+                    None => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("Invalid master index in {} ({mast_index}, {refr_index})", cell.editor_id()),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let Some(header) = self.header_mut() else {
+            return Writer::error("Missing plugin header");
+        };
+        header.masters = masters;
+
+        for cell in self.objects_of_type_mut::<Cell>() {
+            cell.references = std::mem::take(&mut cell.references)
+                .into_iter()
+                .map(|((mast_index, refr_index), mut reference)| {
+                    reference.mast_index = remap[mast_index as usize].unwrap_or_default();
+                    ((reference.mast_index, refr_index), reference)
+                })
+                .collect();
+            cell.moved_references = std::mem::take(&mut cell.moved_references)
+                .into_iter()
+                .map(|((mast_index, refr_index), grid)| ((remap[mast_index as usize].unwrap_or_default(), refr_index), grid))
+                .collect();
+        }
+
+        Ok(())
+    }
+
+    /// Append a master to this plugin, or update its file size if it is already a master.
+    pub fn add_master(&mut self, name: &str, size: u64) -> io::Result<()> {
+        let mut masters = self.masters().to_vec();
+        if let Some((_, master_size)) = masters.iter_mut().find(|(other, _)| other.eq_ignore_ascii_case(name)) {
+            *master_size = size;
+        } else {
+            masters.push((name.to_string(), size));
+        }
+        self.set_masters(masters)
+    }
+
+    /// Append the plugin at `path` as a master, using its current file size.
+    pub fn add_master_from_path(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let size = std::fs::metadata(path)?.len();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        self.add_master(&name, size)
+    }
+
+    /// Remove a master from this plugin.
+    ///
+    /// Returns an error if any references still belong to the master.
+    pub fn remove_master(&mut self, name: &str) -> io::Result<()> {
+        let mut masters = self.masters().to_vec();
+        masters.retain(|(other, _)| !other.eq_ignore_ascii_case(name));
+        self.set_masters(masters)
+    }
+
+    /// Update the stored file sizes of all masters from the files in `directory`.
+    pub fn refresh_master_sizes(&mut self, directory: impl AsRef<Path>) -> io::Result<()> {
+        let directory = directory.as_ref();
+        if let Some(header) = self.header_mut() {
+            for (name, size) in &mut header.masters {
+                *size = std::fs::metadata(directory.join(&*name))?.len();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_plugin() -> Plugin {
+        let header = Header {
+            masters: vec![("Morrowind.esm".into(), 1), ("Tribunal.esm".into(), 2)],
+            ..default()
+        };
+        let mut cell = Cell::default();
+        for (indices, id) in [((0, 1), "plugin_ref"), ((1, 1), "morrowind_ref"), ((2, 1), "tribunal_ref")] {
+            let reference = Reference {
+                id: id.into(),
+                mast_index: indices.0,
+                refr_index: indices.1,
+                moved_cell: Some((4, 5)),
+                ..default()
+            };
+            cell.references.insert(indices, reference);
+        }
+        cell.moved_references.insert((2, 2), (6, 7));
+        Plugin {
+            objects: vec![header.into(), cell.into()],
+            ..default()
+        }
+    }
+
+    fn reference_keys(plugin: &Plugin) -> Vec<(u32, u32)> {
+        let cell = plugin.objects_of_type::<Cell>().next().unwrap();
+        let mut keys: Vec<_> = cell.references.keys().copied().collect();
+        keys.sort_unstable();
+        for (key, reference) in &cell.references {
+            assert_eq!(key.0, reference.mast_index);
+        }
+        keys
+    }
+
+    #[test]
+    fn reorder_masters() {
+        let mut plugin = test_plugin();
+        plugin
+            .set_masters(vec![("tribunal.esm".into(), 2), ("morrowind.esm".into(), 1)])
+            .unwrap();
+        assert_eq!(reference_keys(&plugin), [(0, 1), (1, 1), (2, 1)]);
+
+        let cell = plugin.objects_of_type::<Cell>().next().unwrap();
+        assert_eq!(cell.references[&(1, 1)].id, "tribunal_ref");
+        assert_eq!(cell.references[&(2, 1)].id, "morrowind_ref");
+        assert_eq!(cell.references[&(2, 1)].moved_cell, Some((4, 5)));
+        assert_eq!(cell.moved_references[&(1, 2)], (6, 7));
+    }
+
+    #[test]
+    fn add_master() {
+        let mut plugin = test_plugin();
+        plugin.add_master("Bloodmoon.esm", 3).unwrap();
+        plugin.add_master("MORROWIND.ESM", 4).unwrap();
+        assert_eq!(plugin.masters().len(), 3);
+        assert_eq!(plugin.masters()[0].1, 4);
+        assert_eq!(reference_keys(&plugin), [(0, 1), (1, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn remove_master() {
+        let mut plugin = test_plugin();
+        plugin.add_master("Bloodmoon.esm", 3).unwrap();
+        plugin
+            .set_masters(vec![
+                ("Morrowind.esm".into(), 1),
+                ("Bloodmoon.esm".into(), 3),
+                ("Tribunal.esm".into(), 2),
+            ])
+            .unwrap();
+        assert_eq!(reference_keys(&plugin), [(0, 1), (1, 1), (3, 1)]);
+
+        plugin.remove_master("Bloodmoon.esm").unwrap();
+        assert_eq!(reference_keys(&plugin), [(0, 1), (1, 1), (2, 1)]);
+
+        let error = plugin.remove_master("Morrowind.esm").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(plugin.masters().len(), 2);
+        assert_eq!(reference_keys(&plugin), [(0, 1), (1, 1), (2, 1)]);
+    }
+}
@@ -84,3 +84,12 @@ impl ObjectInfo for TES3Object {
         }
     }
 }
+
+impl ObjectInfo for UnknownRecord {
+    fn object_flags(&self) -> &ObjectFlags {
+        &self.flags
+    }
+    fn object_flags_mut(&mut self) -> &mut ObjectFlags {
+        &mut self.flags
+    }
+}
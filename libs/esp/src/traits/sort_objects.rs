@@ -50,8 +50,10 @@ impl Plugin {
                 TES3Object::SoundGen(obj)         => (38, obj.sort_hint(), &*obj.id),
                 TES3Object::Dialogue(obj)         => (39, obj.sort_hint(), ""), // Preserve DIAL/INFO order
                 TES3Object::DialogueInfo(obj)     => (39, obj.sort_hint(), ""), // ^
+                TES3Object::Unknown(obj)          => (40, obj.sort_hint(), ""), // Restored below
             }
         });
+        keep_unknown_positions(&mut indices, &self.objects);
         unsafe { apply_isort(&mut indices, &mut self.objects) };
     }
 }
@@ -98,6 +100,20 @@ where
     indices.sort_by_key(|&i| function(&subject[i]));
 }
 
+/// Adjust the sorted `indices` so that unknown records keep their original positions, with the
+/// known records sorted around them.
+fn keep_unknown_positions(indices: &mut [usize], objects: &[TES3Object]) {
+    let is_unknown = |i: usize| matches!(objects[i], TES3Object::Unknown(_));
+    if !(0..objects.len()).any(is_unknown) {
+        return;
+    }
+    let mut known = indices.iter().copied().filter(|&i| !is_unknown(i));
+    let adjusted: Vec<_> = (0..indices.len())
+        .map(|i| if is_unknown(i) { i } else { known.next().unwrap_or(i) })
+        .collect();
+    indices.copy_from_slice(&adjusted);
+}
+
 /// Sort `subject` in place using the order specified by `indices`.
 ///
 /// Note: use `isort` function to compose a valid `indices` slice.
@@ -140,6 +156,7 @@ mod tests {
                 LandscapeTexture { index: 2, ..default() }.into(),
                 LandscapeTexture { index: 0, ..default() }.into(),
             ],
+            ..default()
         };
         plugin.sort_objects();
 
@@ -148,6 +165,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn sort_unknown() {
+        let unknown = |tag| TES3Object::Unknown(UnknownRecord { tag, ..default() });
+        let mut plugin = Plugin {
+            objects: vec![
+                Static {
+                    id: "b".into(),
+                    ..default()
+                }
+                .into(),
+                unknown(*b"XXXX"),
+                GameSetting::default().into(),
+                Static {
+                    id: "a".into(),
+                    ..default()
+                }
+                .into(),
+                unknown(*b"YYYY"),
+            ],
+            ..default()
+        };
+        plugin.sort_objects();
+
+        let tags: Vec<_> = plugin.objects.iter().map(TypeInfo::tag_str).collect();
+        assert_eq!(tags, ["GMST", "XXXX", "STAT", "STAT", "YYYY"]);
+        let ids: Vec<_> = plugin.objects_of_type::<Static>().map(|obj| &*obj.id).collect();
+        assert_eq!(ids, ["a", "b"]);
+    }
+
     #[test]
     fn sort_identity() {
         let mut indices = vec![0, 1, 2, 3, 4];
@@ -1,19 +1,19 @@
 use crate::prelude::*;
 
 pub trait TypeInfo {
-    fn tag(&self) -> &'static [u8; 4];
-    fn tag_str(&self) -> &'static str;
+    fn tag(&self) -> &[u8; 4];
+    fn tag_str(&self) -> &str;
     fn type_name(&self) -> &'static str;
 }
 
 delegate! {
     impl TypeInfo {
         #[inline(always)]
-        fn tag(&self) -> &'static [u8; 4] {
+        fn tag(&self) -> &[u8; 4] {
             Self::TAG
         }
         #[inline(always)]
-        fn tag_str(&self) -> &'static str {
+        fn tag_str(&self) -> &str {
             Self::TAG_STR
         }
         #[inline(always)]
@@ -24,14 +24,14 @@ delegate! {
 }
 
 impl TypeInfo for TES3Object {
-    fn tag(&self) -> &'static [u8; 4] {
+    fn tag(&self) -> &[u8; 4] {
         delegate! {
             match self {
                 inner => inner.tag()
             }
         }
     }
-    fn tag_str(&self) -> &'static str {
+    fn tag_str(&self) -> &str {
         delegate! {
             match self {
                 inner => inner.tag_str()
@@ -46,3 +46,15 @@ impl TypeInfo for TES3Object {
         }
     }
 }
+
+impl TypeInfo for UnknownRecord {
+    fn tag(&self) -> &[u8; 4] {
+        &self.tag
+    }
+    fn tag_str(&self) -> &str {
+        std::str::from_utf8(self.tag()).unwrap_or("????")
+    }
+    fn type_name(&self) -> &'static str {
+        "Unknown"
+    }
+}
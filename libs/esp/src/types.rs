@@ -12,37 +12,51 @@ mod bodypart;
 mod book;
 mod cell;
 mod class;
+mod clean;
 mod clothing;
 mod container;
 mod creature;
 mod dialogue;
 mod dialogueinfo;
+mod diff;
 mod door;
 mod effect;
 mod enchanting;
+mod encoding;
 mod enums;
 mod faction;
 mod flags;
 mod gamesetting;
 mod globalvariable;
 mod header;
+mod heightmap;
+mod indexedplugin;
 mod ingredient;
+mod integrity;
 mod landscape;
 mod landscapetexture;
 mod leveledcreature;
 mod leveleditem;
 mod light;
+mod loaderror;
+mod loadorder;
 mod lockpick;
 mod magiceffect;
+mod merge;
 mod miscitem;
 mod npc;
 mod pathgrid;
 mod plugin;
+mod pluginindex;
+mod pluginreader;
 mod probe;
+mod quest;
 mod race;
 mod reference;
 mod region;
 mod repairitem;
+mod savegame;
+mod saverecord;
 mod script;
 mod skill;
 mod sound;
@@ -51,6 +65,12 @@ mod spell;
 mod startscript;
 mod static_;
 mod string;
+mod terrain;
+mod terraineditor;
+mod texturegrid;
+mod topic;
+mod unknown;
+mod validate;
 mod weapon;
 
 pub use activator::*;
@@ -65,11 +85,13 @@ pub use bodypart::*;
 pub use book::*;
 pub use cell::*;
 pub use class::*;
+pub use clean::*;
 pub use clothing::*;
 pub use container::*;
 pub use creature::*;
 pub use dialogue::*;
 pub use dialogueinfo::*;
+pub use diff::*;
 pub use door::*;
 pub use effect::*;
 pub use enchanting::*;
@@ -79,23 +101,34 @@ pub use flags::*;
 pub use gamesetting::*;
 pub use globalvariable::*;
 pub use header::*;
+pub use heightmap::*;
+pub use indexedplugin::*;
 pub use ingredient::*;
+pub use integrity::*;
 pub use landscape::*;
 pub use landscapetexture::*;
 pub use leveledcreature::*;
 pub use leveleditem::*;
 pub use light::*;
+pub use loaderror::*;
+pub use loadorder::*;
 pub use lockpick::*;
 pub use magiceffect::*;
+pub use merge::*;
 pub use miscitem::*;
 pub use npc::*;
 pub use pathgrid::*;
 pub use plugin::*;
+pub use pluginindex::*;
+pub use pluginreader::*;
 pub use probe::*;
+pub use quest::*;
 pub use race::*;
 pub use reference::*;
 pub use region::*;
 pub use repairitem::*;
+pub use savegame::*;
+pub use saverecord::*;
 pub use script::*;
 pub use skill::*;
 pub use sound::*;
@@ -104,6 +137,12 @@ pub use spell::*;
 pub use startscript::*;
 pub use static_::*;
 pub use string::*;
+pub use terrain::*;
+pub use terraineditor::*;
+pub use texturegrid::*;
+pub use topic::*;
+pub use unknown::*;
+pub use validate::*;
 pub use weapon::*;
 
 #[rustfmt::skip]
@@ -153,4 +192,5 @@ pub enum TES3Object {
     #[tag("PGRD")] PathGrid(PathGrid),
     #[tag("DIAL")] Dialogue(Dialogue),
     #[tag("INFO")] DialogueInfo(DialogueInfo),
+    Unknown(UnknownRecord),
 }
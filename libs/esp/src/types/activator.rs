@@ -9,6 +9,7 @@ pub struct Activator {
     pub name: String,
     pub script: String,
     pub mesh: String,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 impl Load for Activator {
@@ -37,7 +38,7 @@ impl Load for Activator {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -12,6 +12,7 @@ pub struct Alchemy {
     pub icon: String,
     pub effects: Vec<Effect>,
     pub data: AlchemyData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -59,7 +60,7 @@ impl Load for Alchemy {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -11,6 +11,7 @@ pub struct Apparatus {
     pub mesh: String,
     pub icon: String,
     pub data: ApparatusData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -55,7 +56,7 @@ impl Load for Apparatus {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -13,6 +13,7 @@ pub struct Armor {
     pub enchanting: String,
     pub biped_objects: Vec<BipedObject>,
     pub data: ArmorData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -65,7 +66,7 @@ impl Load for Armor {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -10,6 +10,7 @@ pub struct Birthsign {
     pub texture: String,
     pub description: String,
     pub spells: Vec<String>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 impl Load for Birthsign {
@@ -41,7 +42,7 @@ impl Load for Birthsign {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -13,6 +13,7 @@ pub struct Book {
     pub enchanting: String,
     pub text: String,
     pub data: BookData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -64,7 +65,7 @@ impl Load for Book {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -1,6 +1,15 @@
+// external imports
+use glam::Affine3A;
+
 // internal imports
 use crate::prelude::*;
 
+/// The size of an exterior cell, in world units.
+const CELL_SIZE: f32 = 8192.0;
+
+/// The largest `refr_index` that can be saved.
+const MAX_REFR_INDEX: u32 = 0xFFFFFF;
+
 #[esp_meta]
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Cell {
@@ -13,6 +22,20 @@ pub struct Cell {
     pub atmosphere_data: Option<AtmosphereData>,
     #[cfg_attr(feature = "serde", serde(with = "crate::features::serde::cell_references"))]
     pub references: HashMap<(u32, u32), Reference>,
+    /// Moved references that are not defined in this cell, mapped to the grid they moved to.
+    ///
+    /// These are references of a master that were moved without a copy of the reference being
+    /// saved to this cell, see [`LoadOrder::cell_references`] for resolving them.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            skip_serializing_if = "HashMap::is_empty",
+            with = "crate::features::serde::moved_references"
+        )
+    )]
+    pub moved_references: HashMap<(u32, u32), (i32, i32)>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -119,7 +142,7 @@ impl Load for Cell {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -130,14 +153,11 @@ impl Load for Cell {
                 reference.moved_cell = Some(moved_cell);
             } else {
                 // Since we don't require loading all master files, there is potential that
-                // the indices refer to a reference not defined in the current plugin. We've
-                // no choice but to trigger an error in this case. Note that the TESCS always
-                // copies the associated reference to the plugin, which prevents this from
-                // happening. Other tools may not be so nice.
-                Reader::error(format!(
-                    "Unable to resolve moved reference {:?} for cell {} {:?}",
-                    indices, this.name, this.data.grid
-                ))?;
+                // the indices refer to a reference not defined in the current plugin. Note
+                // that the TESCS always copies the associated reference to the plugin, which
+                // prevents this from happening. Other tools may not be so nice, so we keep
+                // these separately to be resolved against the masters later.
+                this.moved_references.insert(indices, moved_cell);
             }
         }
 
@@ -212,6 +232,23 @@ impl Save for Cell {
             // REFR
             stream.save(reference)?;
         }
+        // Moved references without a FRMR go last, as loading assigns a MVRF to the next FRMR.
+        let mut moved_references: Vec<_> = self
+            .moved_references
+            .iter()
+            .filter(|(key, _)| !self.references.contains_key(*key))
+            .collect();
+        moved_references.sort_unstable();
+        for (key, value) in moved_references {
+            // MVRF
+            stream.save(b"MVRF")?;
+            stream.save(&4u32)?;
+            stream.save(&pack(*key))?;
+            // CNDT
+            stream.save(b"CNDT")?;
+            stream.save(&8u32)?;
+            stream.save(value)?;
+        }
         Ok(())
     }
 }
@@ -237,6 +274,106 @@ impl Cell {
         self.region.as_deref().unwrap_or("Wilderness")
     }
 
+    /// Add a new reference to the object with the given id, returning its indices.
+    ///
+    /// The `refr_index` must not be used by any other reference of the plugin, see
+    /// [`Plugin::next_refr_index`]. Fails if it is already used by a reference of this cell, or if
+    /// it is larger than the 24 bits that can be saved. The reference is temporary, clear its
+    /// `temporary` field to make it persistent.
+    pub fn add_reference(&mut self, refr_index: u32, id: &str, transform: Affine3A) -> io::Result<(u32, u32)> {
+        if refr_index > MAX_REFR_INDEX {
+            let message = format!("Reference index out of range: {refr_index}");
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+        if self.references.contains_key(&(0, refr_index)) {
+            let message = format!(
+                "Duplicate reference index {refr_index} for cell {} {:?}",
+                self.name, self.data.grid
+            );
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+        let mut reference = Reference {
+            refr_index,
+            id: id.into(),
+            temporary: true,
+            ..default()
+        };
+        reference.set_transform(transform);
+        self.references.insert((0, refr_index), reference);
+        Ok((0, refr_index))
+    }
+
+    /// Move a reference of this cell to a new transform.
+    ///
+    /// References stay in the cell that defines them. When an exterior reference is moved into
+    /// another exterior cell, its `moved_cell` is set to that cell's grid, which is saved as the
+    /// `MVRF` and `CNDT` subrecords. References can not be moved between interior cells, instead
+    /// remove them and add a new reference to the other cell.
+    pub fn move_reference(&mut self, indices: (u32, u32), transform: Affine3A) -> io::Result<()> {
+        let grid = self.exterior_coords();
+        let Some(reference) = self.references.get_mut(&indices) else {
+            let message = format!("Unknown reference {indices:?} for cell {} {:?}", self.name, self.data.grid);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        };
+        reference.set_transform(transform);
+        if let Some(grid) = grid {
+            let [x, y, _] = reference.translation;
+            #[allow(clippy::cast_possible_truncation)]
+            let new_grid = ((x / CELL_SIZE).floor() as i32, (y / CELL_SIZE).floor() as i32);
+            // Master references keep their `moved_cell`, to undo any move made by the master.
+            reference.moved_cell = if new_grid == grid && indices.0 == 0 {
+                None
+            } else {
+                Some(new_grid)
+            };
+        }
+        Ok(())
+    }
+
+    /// Delete a reference of this cell, returning the previous reference.
+    ///
+    /// References defined by this plugin are removed. References defined by a master are
+    /// replaced with a deleted reference, which removes them from the game when saved. Master
+    /// references must already be in this cell, see [`Cell::delete_master_reference`] for ones
+    /// that are not.
+    pub fn delete_reference(&mut self, indices: (u32, u32)) -> Option<Reference> {
+        if indices.0 == 0 {
+            return self.references.remove(&indices);
+        }
+        let reference = self.references.get_mut(&indices)?;
+        let deleted = Reference {
+            mast_index: reference.mast_index,
+            refr_index: reference.refr_index,
+            id: reference.id.clone(),
+            temporary: reference.temporary,
+            moved_cell: reference.moved_cell,
+            deleted: Some(true),
+            ..default()
+        };
+        Some(std::mem::replace(reference, deleted))
+    }
+
+    /// Delete a reference defined by a master, that this cell may not contain yet.
+    ///
+    /// The `master` reference is the reference as loaded from the master file, and `mast_index`
+    /// the position of that file in this plugin's masters (starting from 1). A deleted reference
+    /// is added in its place, returns the reference it replaced if any.
+    pub fn delete_master_reference(&mut self, mast_index: u32, master: &Reference) -> io::Result<Option<Reference>> {
+        if mast_index == 0 {
+            let message = format!("Invalid master index for reference {}: {mast_index}", master.id);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+        let deleted = Reference {
+            mast_index,
+            refr_index: master.refr_index,
+            id: master.id.clone(),
+            temporary: master.temporary,
+            deleted: Some(true),
+            ..default()
+        };
+        Ok(self.references.insert((mast_index, master.refr_index), deleted))
+    }
+
     fn references_sorted(&self) -> Vec<(&(u32, u32), &Reference)> {
         let mut references: Vec<_> = self.references.iter().collect();
 
@@ -260,15 +397,136 @@ impl Cell {
     }
 }
 
+impl Plugin {
+    /// The next unused `refr_index` for references defined by this plugin.
+    ///
+    /// Fails if the plugin already uses the largest `refr_index` that can be saved.
+    pub fn next_refr_index(&self) -> io::Result<u32> {
+        let max = self
+            .objects_of_type::<Cell>()
+            .flat_map(|cell| cell.references.keys())
+            .filter(|(mast_index, _)| *mast_index == 0)
+            .map(|(_, refr_index)| *refr_index)
+            .max()
+            .unwrap_or(0);
+        if max >= MAX_REFR_INDEX {
+            let message = format!("No unused refr_index: {max} is the largest that can be saved");
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+        Ok(max + 1)
+    }
+}
+
 const fn unpack(packed_indices: u32) -> (u32, u32) {
     let mast_index = packed_indices >> 24;
-    let refr_index = packed_indices & 0xFFFFFF;
+    let refr_index = packed_indices & MAX_REFR_INDEX;
     (mast_index, refr_index)
 }
 
 const fn pack(packed_indices: (u32, u32)) -> u32 {
     let (mast_index, refr_index) = packed_indices;
     debug_assert!(mast_index <= 0xFF);
-    debug_assert!(refr_index <= 0xFFFFFF);
+    debug_assert!(refr_index <= MAX_REFR_INDEX);
     refr_index | (mast_index << 24)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec3;
+
+    fn test_plugin() -> Plugin {
+        let mut cell = Cell {
+            data: CellData {
+                grid: (1, 2),
+                ..default()
+            },
+            ..default()
+        };
+        let master_reference = Reference {
+            mast_index: 1,
+            refr_index: 7,
+            id: "master_door".into(),
+            ..default()
+        };
+        cell.references.insert((1, 7), master_reference);
+        let transform = Affine3A::from_translation(Vec3::new(9000.0, 17000.0, 0.0));
+        cell.add_reference(5, "plugin_rock", transform).unwrap();
+
+        let mut plugin = Plugin::new();
+        plugin.objects.push(cell.into());
+        plugin
+    }
+
+    #[test]
+    fn add_reference() {
+        let mut plugin = test_plugin();
+        assert_eq!(plugin.next_refr_index().unwrap(), 6);
+
+        let refr_index = plugin.next_refr_index().unwrap();
+        let cell = plugin.objects_of_type_mut::<Cell>().next().unwrap();
+        let indices = cell.add_reference(refr_index, "plugin_tree", Affine3A::IDENTITY).unwrap();
+        assert_eq!(indices, (0, 6));
+        assert!(cell.references[&indices].temporary);
+        assert_eq!(plugin.next_refr_index().unwrap(), 7);
+
+        // Used and out of range indices are rejected, without replacing the existing reference.
+        let cell = plugin.objects_of_type_mut::<Cell>().next().unwrap();
+        assert!(cell.add_reference(6, "plugin_other", Affine3A::IDENTITY).is_err());
+        assert_eq!(cell.references[&(0, 6)].id, "plugin_tree");
+        assert!(cell
+            .add_reference(MAX_REFR_INDEX + 1, "plugin_other", Affine3A::IDENTITY)
+            .is_err());
+
+        cell.add_reference(MAX_REFR_INDEX, "plugin_last", Affine3A::IDENTITY).unwrap();
+        assert!(plugin.next_refr_index().is_err());
+    }
+
+    #[test]
+    fn move_reference() {
+        let mut plugin = test_plugin();
+        let cell = plugin.objects_of_type_mut::<Cell>().next().unwrap();
+
+        let transform = Affine3A::from_translation(Vec3::new(-100.0, 17000.0, 0.0));
+        cell.move_reference((0, 5), transform).unwrap();
+        assert_eq!(cell.references[&(0, 5)].moved_cell, Some((-1, 2)));
+        assert_eq!(cell.references[&(0, 5)].translation.to_vec(), [-100.0, 17000.0, 0.0]);
+
+        let transform = Affine3A::from_translation(Vec3::new(8200.0, 16400.0, 0.0));
+        cell.move_reference((0, 5), transform).unwrap();
+        assert_eq!(cell.references[&(0, 5)].moved_cell, None);
+        cell.move_reference((1, 7), transform).unwrap();
+        assert_eq!(cell.references[&(1, 7)].moved_cell, Some((1, 2)));
+
+        assert!(cell.move_reference((0, 8), transform).is_err());
+    }
+
+    #[test]
+    fn delete_reference() {
+        let mut plugin = test_plugin();
+        let cell = plugin.objects_of_type_mut::<Cell>().next().unwrap();
+
+        assert_eq!(cell.delete_reference((0, 5)).unwrap().id, "plugin_rock");
+        assert!(!cell.references.contains_key(&(0, 5)));
+
+        assert_eq!(cell.delete_reference((1, 7)).unwrap().id, "master_door");
+        let reference = &cell.references[&(1, 7)];
+        assert!(reference.deleted());
+        assert_eq!(reference.id, "master_door");
+
+        assert!(cell.delete_reference((0, 8)).is_none());
+        assert!(cell.delete_reference((1, 9)).is_none());
+
+        // Master references not in the cell yet are deleted through the master's reference.
+        let master = Reference {
+            refr_index: 9,
+            id: "master_chest".into(),
+            ..default()
+        };
+        assert!(cell.delete_master_reference(1, &master).unwrap().is_none());
+        let reference = &cell.references[&(1, 9)];
+        assert!(reference.deleted());
+        assert_eq!((reference.mast_index, reference.id.as_str()), (1, "master_chest"));
+        assert!(cell.delete_master_reference(0, &master).is_err());
+    }
+}
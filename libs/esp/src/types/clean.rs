@@ -0,0 +1,357 @@
+// rust std imports
+use std::fmt::{self, Display};
+
+// internal imports
+use crate::prelude::*;
+
+/// What [`Plugin::clean`] did to a record, and why.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CleanAction {
+    /// The record was identical to its master, and was removed.
+    IdenticalRecord,
+    /// The game setting was identical to its master (an "evil GMST"), and was removed.
+    EvilGameSetting,
+    /// The cell header was identical to its master and it had no references left, so was removed.
+    JunkCell,
+    /// A cell subrecord was identical to its master, and was removed.
+    IdenticalCellSubrecord { tag: [u8; 4] },
+    /// A reference was identical to its master, and was removed.
+    IdenticalReference { mast_index: u32, refr_index: u32 },
+}
+
+/// A single change made by [`Plugin::clean`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CleanEntry {
+    /// The tag of the cleaned record.
+    pub tag: [u8; 4],
+    /// The editor id of the cleaned record.
+    pub id: String,
+    pub action: CleanAction,
+}
+
+/// The changes made by [`Plugin::clean`], in the order they were made.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CleanReport {
+    pub entries: Vec<CleanEntry>,
+}
+
+impl Plugin {
+    /// Remove records that are identical to their definitions in the plugin's masters.
+    ///
+    /// The `load_order` must contain the masters of this plugin (see [`Plugin::masters`]), any
+    /// other plugins in the load order are ignored. Cleaning removes:
+    ///
+    /// - Records that are identical to their master, including "evil GMSTs". Dialogues are only
+    ///   removed along with all of their infos.
+    /// - References that are identical to their master. Moved references are never removed.
+    /// - `WHGT` and `AMBI` subrecords of interior cells that are identical to their master.
+    /// - Cells whose header is identical to their master, once they have no references left.
+    ///
+    /// The `MODIFIED` flag is ignored when comparing records, and records flagged as `DELETED`
+    /// are never removed.
+    pub fn clean(&mut self, load_order: &LoadOrder) -> CleanReport {
+        // The load order positions of this plugin's masters.
+        let masters: Vec<Option<usize>> = self.masters().iter().map(|(name, _)| load_order.position(name)).collect();
+        let is_master = |name: &str| load_order.position(name).is_some_and(|i| masters.contains(&Some(i)));
+
+        let mut report = CleanReport::default();
+
+        // Indices of the objects to be removed.
+        let mut removed = vec![false; self.objects.len()];
+
+        for (i, object) in self.objects.iter_mut().enumerate() {
+            let Some(key) = ObjectKey::from_object(object) else {
+                continue;
+            };
+            let Some((_, master)) = load_order.overrides(&key).filter(|(name, _)| is_master(name)).last() else {
+                continue;
+            };
+            if let TES3Object::Cell(cell) = object {
+                let references = load_order.cell_references_filtered(&key, |i| masters.contains(&Some(i)));
+                removed[i] = clean_cell(cell, master, &references, &masters, &mut report);
+            } else if is_identical(object, master) {
+                // Dialogues are handled below, once their infos are known.
+                removed[i] = true;
+                match object {
+                    TES3Object::Dialogue(_) => {}
+                    TES3Object::GameSetting(_) => report.push(object, CleanAction::EvilGameSetting),
+                    _ => report.push(object, CleanAction::IdenticalRecord),
+                }
+            }
+        }
+
+        // Keep dialogues that still have infos.
+        for (i, object) in self.objects.iter().enumerate() {
+            if !(matches!(object, TES3Object::Dialogue(_)) && removed[i]) {
+                continue;
+            }
+            let infos = self.objects[i + 1..]
+                .iter()
+                .take_while(|object| matches!(object, TES3Object::DialogueInfo(_)))
+                .count();
+            if removed[i + 1..=i + infos].iter().all(|removed| *removed) {
+                report.push(object, CleanAction::IdenticalRecord);
+            } else {
+                removed[i] = false;
+            }
+        }
+
+        let mut removed = removed.into_iter();
+        self.objects.retain(|_| !removed.next().unwrap_or_default());
+
+        report
+    }
+}
+
+/// Clean the references and subrecords of a cell, returning whether the cell should be removed.
+fn clean_cell(
+    cell: &mut Cell,
+    master: &TES3Object,
+    master_references: &HashMap<(u32, u32), Reference>,
+    masters: &[Option<usize>],
+    report: &mut CleanReport,
+) -> bool {
+    let TES3Object::Cell(master) = master else {
+        return false;
+    };
+
+    let mut identical = vec![];
+    for (&(mast_index, refr_index), reference) in &cell.references {
+        if reference.moved_cell.is_some() || mast_index == 0 {
+            continue;
+        }
+        // Master references are keyed by load order index, which starts at 1.
+        let Some(Some(index)) = masters.get(mast_index as usize - 1) else {
+            continue;
+        };
+        let Ok(index) = u32::try_from(index + 1) else {
+            continue;
+        };
+        let Some(master_reference) = master_references.get(&(index, refr_index)) else {
+            continue;
+        };
+        let mut master_reference = master_reference.clone();
+        master_reference.mast_index = mast_index;
+        if master_reference == *reference {
+            identical.push((mast_index, refr_index));
+        }
+    }
+    identical.sort_unstable();
+
+    for (mast_index, refr_index) in identical {
+        cell.references.remove(&(mast_index, refr_index));
+        let action = CleanAction::IdenticalReference { mast_index, refr_index };
+        report.push(cell, action);
+    }
+
+    if cell.deleted() {
+        return false;
+    }
+
+    if cell.references.is_empty() && cell.moved_references.is_empty() && is_identical_header(cell, master) {
+        report.push(cell, CleanAction::JunkCell);
+        return true;
+    }
+
+    if cell.is_interior() {
+        if cell.water_height.is_some() && cell.water_height == master.water_height {
+            cell.water_height = None;
+            report.push(cell, CleanAction::IdenticalCellSubrecord { tag: *b"WHGT" });
+        }
+        if cell.atmosphere_data.is_some() && cell.atmosphere_data == master.atmosphere_data {
+            cell.atmosphere_data = None;
+            report.push(cell, CleanAction::IdenticalCellSubrecord { tag: *b"AMBI" });
+        }
+    }
+
+    false
+}
+
+/// Whether two sets of object flags are equal, ignoring the `MODIFIED` flag.
+fn is_identical_flags(a: ObjectFlags, b: ObjectFlags) -> bool {
+    a.symmetric_difference(b).difference(ObjectFlags::MODIFIED).is_empty()
+}
+
+fn is_identical(object: &TES3Object, master: &TES3Object) -> bool {
+    if object.deleted() || !is_identical_flags(*object.object_flags(), *master.object_flags()) {
+        return false;
+    }
+    if object.object_flags() == master.object_flags() {
+        return object == master;
+    }
+    let mut object = object.clone();
+    *object.object_flags_mut() = *master.object_flags();
+    object == *master
+}
+
+fn is_identical_header(cell: &Cell, master: &Cell) -> bool {
+    is_identical_flags(cell.flags, master.flags)
+        && cell.name == master.name
+        && cell.data == master.data
+        && cell.region == master.region
+        && cell.map_color == master.map_color
+        && cell.water_height == master.water_height
+        && cell.atmosphere_data == master.atmosphere_data
+        && cell.unknown_subrecords == master.unknown_subrecords
+}
+
+impl CleanReport {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn push<T: EditorId + TypeInfo>(&mut self, object: &T, action: CleanAction) {
+        self.entries.push(CleanEntry {
+            tag: *object.tag(),
+            id: object.editor_id().into_owned(),
+            action,
+        });
+    }
+}
+
+impl Display for CleanAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CleanAction::IdenticalRecord => write!(f, "removed, identical to master"),
+            CleanAction::EvilGameSetting => write!(f, "removed, evil GMST"),
+            CleanAction::JunkCell => write!(f, "removed, junk cell"),
+            CleanAction::IdenticalCellSubrecord { tag } => {
+                write!(f, "removed {}, identical to master", tag.to_str_lossy())
+            }
+            CleanAction::IdenticalReference { mast_index, refr_index } => {
+                write!(f, "removed reference ({mast_index}, {refr_index}), identical to master")
+            }
+        }
+    }
+}
+
+impl Display for CleanReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{} {}: {}", entry.tag.to_str_lossy(), entry.id, entry.action)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_plugin(masters: &[&str], objects: Vec<TES3Object>) -> Plugin {
+        let header = Header {
+            masters: masters.iter().map(|name| ((*name).into(), 0)).collect(),
+            ..default()
+        };
+        Plugin {
+            objects: std::iter::once(header.into()).chain(objects).collect(),
+            ..default()
+        }
+    }
+
+    fn test_cell(name: &str, mast_index: u32, refr_indices: &[u32]) -> Cell {
+        let mut cell = Cell {
+            name: name.into(),
+            water_height: Some(1.0),
+            ..default()
+        };
+        cell.data.flags = CellFlags::IS_INTERIOR;
+        for &refr_index in refr_indices {
+            let reference = Reference {
+                id: "test_ref".into(),
+                mast_index,
+                refr_index,
+                ..default()
+            };
+            cell.references.insert((mast_index, refr_index), reference);
+        }
+        cell
+    }
+
+    fn test_objects(mast_index: u32) -> Vec<TES3Object> {
+        vec![
+            GameSetting {
+                id: "sTest".into(),
+                ..default()
+            }
+            .into(),
+            Npc {
+                id: "test_npc".into(),
+                ..default()
+            }
+            .into(),
+            Dialogue {
+                id: "test_topic".into(),
+                ..default()
+            }
+            .into(),
+            DialogueInfo {
+                id: "1".into(),
+                ..default()
+            }
+            .into(),
+            test_cell("test_cell", mast_index, &[1, 2]).into(),
+            test_cell("junk_cell", mast_index, &[3]).into(),
+        ]
+    }
+
+    #[test]
+    fn clean() {
+        let master = test_plugin(&[], test_objects(0));
+        let load_order = LoadOrder::from_plugins(vec![("Morrowind.esm".into(), master)]);
+
+        let mut plugin = test_plugin(&["Morrowind.esm"], test_objects(1));
+        for object in &mut plugin.objects {
+            object.set_modified(true);
+            match object {
+                TES3Object::Cell(cell) if cell.name == "test_cell" => {
+                    cell.references.get_mut(&(1, 2)).unwrap().scale = Some(2.0);
+                }
+                TES3Object::Npc(npc) => npc.name = "Changed".into(),
+                _ => {}
+            }
+        }
+
+        let report = plugin.clean(&load_order);
+        let entries: Vec<_> = report
+            .entries
+            .iter()
+            .map(|entry| (entry.id.as_str(), &entry.action))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("sTest", &CleanAction::EvilGameSetting),
+                ("1", &CleanAction::IdenticalRecord),
+                (
+                    "test_cell",
+                    &CleanAction::IdenticalReference {
+                        mast_index: 1,
+                        refr_index: 1
+                    }
+                ),
+                ("test_cell", &CleanAction::IdenticalCellSubrecord { tag: *b"WHGT" }),
+                (
+                    "junk_cell",
+                    &CleanAction::IdenticalReference {
+                        mast_index: 1,
+                        refr_index: 3
+                    }
+                ),
+                ("junk_cell", &CleanAction::JunkCell),
+                ("test_topic", &CleanAction::IdenticalRecord),
+            ]
+        );
+
+        let tags: Vec<_> = plugin.objects.iter().map(TypeInfo::tag_str).collect();
+        assert_eq!(tags, ["TES3", "NPC_", "CELL"]);
+
+        let cell = plugin.objects_of_type::<Cell>().next().unwrap();
+        assert_eq!(cell.water_height, None);
+        assert_eq!(cell.references.keys().collect::<Vec<_>>(), [&(1, 2)]);
+    }
+}
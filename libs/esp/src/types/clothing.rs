@@ -13,6 +13,7 @@ pub struct Clothing {
     pub enchanting: String,
     pub biped_objects: Vec<BipedObject>,
     pub data: ClothingData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -63,7 +64,7 @@ impl Load for Clothing {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -12,6 +12,7 @@ pub struct Container {
     pub encumbrance: f32,
     pub container_flags: ContainerFlags,
     pub inventory: Vec<(i32, FixedString<32>)>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 impl Load for Container {
@@ -52,7 +53,7 @@ impl Load for Container {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -19,6 +19,7 @@ pub struct Creature {
     pub creature_flags: CreatureFlags,
     pub blood_type: u8,
     pub data: CreatureData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -124,7 +125,7 @@ impl Load for Creature {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -7,6 +7,7 @@ pub struct Dialogue {
     pub flags: ObjectFlags,
     pub id: String,
     pub dialogue_type: DialogueType2,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 impl Load for Dialogue {
@@ -35,7 +36,7 @@ impl Load for Dialogue {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -20,6 +20,7 @@ pub struct DialogueInfo {
     pub quest_state: Option<QuestState>,
     pub filters: Vec<Filter>,
     pub script_text: String,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -142,7 +143,7 @@ impl Load for DialogueInfo {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -0,0 +1,185 @@
+// rust std imports
+use std::fmt::{self, Debug, Display};
+
+// internal imports
+use crate::prelude::*;
+
+/// A single changed value within a record.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FieldChange {
+    /// The path of the value, e.g. `Npc.data.level`.
+    pub path: String,
+    /// The previous value, or `None` if it was added.
+    pub old: Option<String>,
+    /// The new value, or `None` if it was removed.
+    pub new: Option<String>,
+}
+
+/// The difference between two definitions of a record, see [`diff`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordDiff<'a> {
+    Added(&'a TES3Object),
+    Removed(&'a TES3Object),
+    Changed {
+        old: &'a TES3Object,
+        new: &'a TES3Object,
+        fields: Vec<FieldChange>,
+    },
+}
+
+/// The differences between two plugins, see [`diff`].
+///
+/// The [`Display`] implementation renders the differences as text, one line per record followed
+/// by one indented line per changed value:
+///
+/// ```text
+/// + NPC_ new_npc
+/// - CELL Old Cell
+/// ~ NPC_ fargoth
+///     Npc.data.level: 5 -> 7
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PluginDiff<'a> {
+    /// The removed and changed records in their old order, followed by the added records.
+    pub records: Vec<RecordDiff<'a>>,
+}
+
+/// Compare two plugins record by record.
+///
+/// Records are paired by their [`ObjectKey`], that is by type and editor id (or by grid for
+/// exterior cells, landscapes, and path grids). Records that exist in both plugins are compared
+/// field by field using [`Diff`].
+pub fn diff<'a>(old: &'a Plugin, new: &'a Plugin) -> PluginDiff<'a> {
+    let key = |object: &TES3Object| {
+        ObjectKey::from_object(object).unwrap_or_else(|| ObjectKey::new(object.tag(), &object.editor_id()))
+    };
+
+    // Maps keys to the new objects, duplicate keys are paired in order of appearance.
+    let mut new_objects: HashMap<ObjectKey, Vec<(usize, &TES3Object)>> = default();
+    for (i, object) in new.objects.iter().enumerate().rev() {
+        new_objects.entry(key(object)).or_default().push((i, object));
+    }
+
+    let mut paired = vec![false; new.objects.len()];
+    let mut records = vec![];
+
+    for old_object in &old.objects {
+        let Some((i, new_object)) = new_objects.get_mut(&key(old_object)).and_then(Vec::pop) else {
+            records.push(RecordDiff::Removed(old_object));
+            continue;
+        };
+        paired[i] = true;
+        if old_object != new_object {
+            let mut path = new_object.type_name().to_owned();
+            let mut fields = vec![];
+            old_object.diff(new_object, &mut path, &mut fields);
+            records.push(RecordDiff::Changed {
+                old: old_object,
+                new: new_object,
+                fields,
+            });
+        }
+    }
+
+    for (object, paired) in new.objects.iter().zip(paired) {
+        if !paired {
+            records.push(RecordDiff::Added(object));
+        }
+    }
+
+    PluginDiff { records }
+}
+
+impl FieldChange {
+    pub fn new<T: Debug>(path: &str, old: Option<T>, new: Option<T>) -> Self {
+        Self {
+            path: path.to_owned(),
+            old: old.map(|value| format!("{value:?}")),
+            new: new.map(|value| format!("{value:?}")),
+        }
+    }
+
+    /// Compare two values as a whole, recording a change if they are not equal.
+    pub(crate) fn compare<T: Debug + PartialEq>(old: &T, new: &T, path: &str, changes: &mut Vec<Self>) {
+        if old != new {
+            changes.push(Self::new(path, Some(old), Some(new)));
+        }
+    }
+}
+
+impl Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let old = self.old.as_deref().unwrap_or("(none)");
+        let new = self.new.as_deref().unwrap_or("(none)");
+        write!(f, "{}: {old} -> {new}", self.path)
+    }
+}
+
+impl RecordDiff<'_> {
+    /// The new definition of the record, or the old definition if it was removed.
+    pub fn object(&self) -> &TES3Object {
+        match self {
+            RecordDiff::Added(object) | RecordDiff::Removed(object) => object,
+            RecordDiff::Changed { new, .. } => new,
+        }
+    }
+
+    /// The changed values, empty for added and removed records.
+    pub fn fields(&self) -> &[FieldChange] {
+        match self {
+            RecordDiff::Changed { fields, .. } => fields,
+            _ => &[],
+        }
+    }
+}
+
+impl Display for RecordDiff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            RecordDiff::Added(_) => '+',
+            RecordDiff::Removed(_) => '-',
+            RecordDiff::Changed { .. } => '~',
+        };
+        let object = self.object();
+        write!(f, "{symbol} {} {}", object.tag_str(), object.editor_id())?;
+        for field in self.fields() {
+            write!(f, "\n    {field}")?;
+        }
+        Ok(())
+    }
+}
+
+impl PluginDiff<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn added(&self) -> impl Iterator<Item = &TES3Object> {
+        self.records.iter().filter_map(|record| match record {
+            RecordDiff::Added(object) => Some(*object),
+            _ => None,
+        })
+    }
+
+    pub fn removed(&self) -> impl Iterator<Item = &TES3Object> {
+        self.records.iter().filter_map(|record| match record {
+            RecordDiff::Removed(object) => Some(*object),
+            _ => None,
+        })
+    }
+
+    pub fn changed(&self) -> impl Iterator<Item = &RecordDiff<'_>> {
+        self.records
+            .iter()
+            .filter(|record| matches!(record, RecordDiff::Changed { .. }))
+    }
+}
+
+impl Display for PluginDiff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for record in &self.records {
+            writeln!(f, "{record}")?;
+        }
+        Ok(())
+    }
+}
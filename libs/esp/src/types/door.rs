@@ -11,6 +11,7 @@ pub struct Door {
     pub mesh: String,
     pub open_sound: String,
     pub close_sound: String,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 impl Load for Door {
@@ -45,7 +46,7 @@ impl Load for Door {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -8,6 +8,7 @@ pub struct Enchanting {
     pub id: String,
     pub effects: Vec<Effect>,
     pub data: EnchantingData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -44,7 +45,7 @@ impl Load for Enchanting {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -0,0 +1,173 @@
+// internal imports
+use crate::prelude::*;
+
+impl Plugin {
+    /// The default encoding, used by the English, French, German, and other western editions.
+    pub fn default_encoding() -> &'static Encoding {
+        WINDOWS_1252
+    }
+
+    /// Guess the encoding of the given plugin bytes.
+    ///
+    /// The candidates are Windows-1252 (western editions), Windows-1250 (Polish and Czech), and
+    /// Windows-1251 (Russian). Each candidate is used to decode the text subrecords of the plugin,
+    /// and scored on whether the decoded letters are plausible: accented latin letters should
+    /// neighbor unaccented latin letters, cyrillic letters should not neighbor latin letters, and
+    /// symbols should be rare. Ties are resolved in favor of Windows-1252.
+    pub fn detect_encoding(bytes: &[u8]) -> &'static Encoding {
+        let candidates = [WINDOWS_1252, WINDOWS_1250, WINDOWS_1251];
+        let mut scores = [0i64; 3];
+
+        for range in Self::record_offsets(bytes, |_| true) {
+            for text in text_subrecords(&bytes[range]) {
+                if text.is_ascii() {
+                    continue;
+                }
+                for (score, encoding) in scores.iter_mut().zip(candidates) {
+                    *score += score_text(&encoding.decode_without_bom_handling(text).0);
+                }
+            }
+        }
+
+        let mut best = 0;
+        for i in 1..candidates.len() {
+            if scores[i] > scores[best] {
+                best = i;
+            }
+        }
+        candidates[best]
+    }
+}
+
+/// Iterate over the subrecords of `record` whose data looks like text, without null terminators.
+fn text_subrecords(record: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut position = RawRecord::HEADER_SIZE;
+    std::iter::from_fn(move || loop {
+        let header = record.get(position..position + 8)?;
+        let len = u32::from_le_bytes(header[4..8].try_into().ok()?) as usize;
+        let data = record.get(position + 8..position + 8 + len)?;
+        position += 8 + len;
+
+        let end = data.iter().rposition(|&byte| byte != 0).map_or(0, |i| i + 1);
+        let data = &data[..end];
+        if !data.is_empty() && data.iter().all(|&byte| byte >= 0x20 || b"\t\r\n".contains(&byte)) {
+            return Some(data);
+        }
+    })
+}
+
+/// Score the plausibility of the non-ascii characters in some decoded text.
+fn score_text(text: &str) -> i64 {
+    let chars: Vec<char> = text.chars().collect();
+    let mut score = 0;
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii() {
+            continue;
+        }
+        if !c.is_alphabetic() {
+            score -= 1;
+            continue;
+        }
+        let mut neighbors = [i.checked_sub(1), Some(i + 1)]
+            .into_iter()
+            .filter_map(|i| chars.get(i?).copied());
+        let plausible = if is_cyrillic(c) {
+            neighbors.all(|n| !n.is_alphabetic() || is_cyrillic(n))
+        } else {
+            neighbors.any(|n| n.is_ascii_alphabetic())
+        };
+        score += if plausible { 1 } else { -1 };
+    }
+
+    score
+}
+
+fn is_cyrillic(c: char) -> bool {
+    matches!(c, '\u{0400}'..='\u{04FF}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_plugin(name: &str, encoding: &'static Encoding) -> Vec<u8> {
+        let mut plugin = Plugin {
+            objects: vec![
+                Header::default().into(),
+                Npc {
+                    id: "test_npc".into(),
+                    name: name.into(),
+                    ..default()
+                }
+                .into(),
+            ],
+            encoding,
+        };
+        plugin.save_bytes().unwrap()
+    }
+
+    #[test]
+    fn detect_encoding() {
+        for (text, encoding) in [
+            ("Café crème", WINDOWS_1252),
+            ("Zażółć gęślą jaźń", WINDOWS_1250),
+            ("Привет, мир", WINDOWS_1251),
+            ("Hello, world", WINDOWS_1252),
+        ] {
+            let bytes = test_plugin(text, encoding);
+            assert_eq!(Plugin::detect_encoding(&bytes), encoding, "{text}");
+
+            let mut plugin = Plugin::new();
+            plugin.load_bytes_detect_encoding(&bytes).unwrap();
+            assert_eq!(plugin.encoding, encoding);
+            assert_eq!(plugin.objects_of_type::<Npc>().next().unwrap().name, text);
+            assert_eq!(plugin.save_bytes().unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn raw_record_encoding() {
+        let bytes = test_plugin("Привет, мир", WINDOWS_1251);
+        let mut reader = PluginReader::from_reader(io::Cursor::new(bytes)).encoding(WINDOWS_1251);
+        let raw = reader.raw_records().nth(1).unwrap().unwrap();
+        assert_eq!(raw.encoding, WINDOWS_1251);
+
+        let npc: Npc = raw.decode().unwrap().try_into().unwrap();
+        assert_eq!(npc.name, "Привет, мир");
+    }
+
+    #[test]
+    fn editor_id_encoding() {
+        let mut plugin = Plugin {
+            objects: vec![
+                Header::default().into(),
+                Npc {
+                    id: "Привет".into(),
+                    ..default()
+                }
+                .into(),
+            ],
+            encoding: WINDOWS_1251,
+        };
+        let bytes = plugin.save_bytes().unwrap();
+
+        // corrupt the size of the NPDT subrecord
+        let mut corrupt = bytes.clone();
+        let npdt = corrupt.windows(4).position(|window| window == b"NPDT").unwrap();
+        corrupt[npdt + 4] = 99;
+        let error = Plugin::new().load_bytes_with_encoding(&corrupt, WINDOWS_1251).unwrap_err();
+        let details = LoadError::from_io_error(&error).unwrap();
+        assert_eq!(details.editor_id.as_deref(), Some("Привет"));
+
+        // rename the NPC_ record to an unknown type
+        let mut unknown = bytes;
+        let npc = unknown.windows(4).position(|window| window == b"NPC_").unwrap();
+        unknown[npc..npc + 4].copy_from_slice(b"XXXX");
+        let mut plugin = Plugin::new();
+        plugin.encoding = WINDOWS_1251;
+        plugin.load_bytes_lenient(&unknown).unwrap();
+        assert_eq!(plugin.objects[1].tag_str(), "XXXX");
+        assert_eq!(plugin.objects[1].editor_id(), "Привет");
+    }
+}
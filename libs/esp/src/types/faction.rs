@@ -10,6 +10,7 @@ pub struct Faction {
     pub rank_names: Vec<String>,
     pub reactions: Vec<FactionReaction>,
     pub data: FactionData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -67,7 +68,7 @@ impl Load for Faction {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -7,6 +7,7 @@ pub struct GameSetting {
     pub flags: ObjectFlags,
     pub id: String,
     pub value: GameSettingValue,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -41,7 +42,7 @@ impl Load for GameSetting {
                     this.value = GameSettingValue::Integer(stream.load()?);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
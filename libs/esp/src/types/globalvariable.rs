@@ -7,6 +7,7 @@ pub struct GlobalVariable {
     pub flags: ObjectFlags,
     pub id: String,
     pub value: GlobalValue,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -43,7 +44,7 @@ impl Load for GlobalVariable {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -16,6 +16,7 @@ pub struct Header {
     pub description: FixedString<256>,
     pub num_objects: u32,
     pub masters: Vec<(String, u64)>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 impl Load for Header {
@@ -44,7 +45,7 @@ impl Load for Header {
                     this.masters.push((master_name, master_size));
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -0,0 +1,354 @@
+// internal imports
+use crate::prelude::*;
+
+/// The number of vertex steps along each side of a landscape.
+const STEPS: usize = 64;
+
+/// A 16-bit grayscale image of landscape heights, one pixel per vertex.
+///
+/// Pixels are heights in steps of 8 units, offset by 32768 (see [`Heightmap::encode_height`]).
+/// Rows go from north to south and columns from west to east, so the image looks like the world
+/// map. Neighboring landscapes share their border pixels, which makes an image of `w` by `h`
+/// landscapes `64 * w + 1` by `64 * h + 1` pixels in size.
+///
+/// ```
+/// use esp::{Heightmap, Plugin};
+///
+/// // A flat 2x2 landscape area at a height of 256 units.
+/// let heightmap = Heightmap {
+///     width: 129,
+///     height: 129,
+///     pixels: vec![Heightmap::encode_height(256.0); 129 * 129],
+/// };
+/// let bytes = heightmap.to_pgm();
+///
+/// let mut plugin = Plugin::new();
+/// plugin.import_heightmap((-2, -2), &Heightmap::from_pgm(&bytes)?)?;
+/// assert_eq!(plugin.export_heightmap((-2, -2), (-1, -1)), heightmap);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Heightmap {
+    pub width: usize,
+    pub height: usize,
+    /// The pixels, row by row from north to south.
+    pub pixels: Vec<u16>,
+}
+
+/// An 8-bit RGB image of landscape vertex colors, one pixel per vertex.
+///
+/// Pixels are laid out like those of a [`Heightmap`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VertexColorMap {
+    pub width: usize,
+    pub height: usize,
+    /// The pixels, row by row from north to south.
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Plugin {
+    /// Export the heights of the landscapes from grid `min` to grid `max` (inclusive).
+    ///
+    /// Vertices without a landscape are given [`TerrainEditor::DEFAULT_HEIGHT`].
+    pub fn export_heightmap(&self, min: (i32, i32), max: (i32, i32)) -> Heightmap {
+        let editor = TerrainEditor::new(self);
+        let (width, height) = image_size(min, max);
+        let pixels = pixel_vertices(min, width, height)
+            .map(|(vx, vy)| editor.height(vx, vy).unwrap_or(TerrainEditor::DEFAULT_HEIGHT))
+            .map(Heightmap::encode_height)
+            .collect();
+        Heightmap { width, height, pixels }
+    }
+
+    /// Import the heights of a heightmap, with its south west corner at grid `min`.
+    ///
+    /// Missing landscapes within the image are created, and the normals of the imported vertices
+    /// and their neighbors are recomputed. Returns the vertices whose heights could not be encoded
+    /// exactly, see [`Landscape::encode_vertex_heights`].
+    pub fn import_heightmap(
+        &mut self,
+        min: (i32, i32),
+        heightmap: &Heightmap,
+    ) -> io::Result<Vec<((i32, i32), ClampedVertex)>> {
+        check_image_size(heightmap.width, heightmap.height, heightmap.pixels.len())?;
+        let mut editor = editor_for_image(self, min, heightmap.width, heightmap.height);
+        for ((vx, vy), &pixel) in pixel_vertices(min, heightmap.width, heightmap.height).zip(&heightmap.pixels) {
+            editor.update_height(vx, vy, Heightmap::decode_height(pixel));
+        }
+        Ok(editor.apply(self))
+    }
+
+    /// Export the vertex colors of the landscapes from grid `min` to grid `max` (inclusive).
+    ///
+    /// Vertices without a landscape or vertex colors are given [`TerrainEditor::DEFAULT_COLOR`].
+    pub fn export_vertex_colors(&self, min: (i32, i32), max: (i32, i32)) -> VertexColorMap {
+        let editor = TerrainEditor::new(self);
+        let (width, height) = image_size(min, max);
+        let pixels = pixel_vertices(min, width, height)
+            .map(|(vx, vy)| editor.color(vx, vy).unwrap_or(TerrainEditor::DEFAULT_COLOR))
+            .collect();
+        VertexColorMap { width, height, pixels }
+    }
+
+    /// Import the vertex colors of an image, with its south west corner at grid `min`.
+    ///
+    /// Missing landscapes within the image are created.
+    pub fn import_vertex_colors(&mut self, min: (i32, i32), colors: &VertexColorMap) -> io::Result<()> {
+        check_image_size(colors.width, colors.height, colors.pixels.len())?;
+        let mut editor = editor_for_image(self, min, colors.width, colors.height);
+        for ((vx, vy), &pixel) in pixel_vertices(min, colors.width, colors.height).zip(&colors.pixels) {
+            editor.update_color(vx, vy, pixel);
+        }
+        editor.apply(self);
+        Ok(())
+    }
+}
+
+impl Heightmap {
+    /// Convert a height to a pixel value, `round(height / 8) + 32768`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn encode_height(height: f32) -> u16 {
+        ((height / 8.0).round() + 32768.0).clamp(0.0, 65535.0) as u16
+    }
+
+    /// Convert a pixel value to a height, the inverse of [`Heightmap::encode_height`].
+    pub fn decode_height(pixel: u16) -> f32 {
+        (f32::from(pixel) - 32768.0) * 8.0
+    }
+
+    /// Encode the heightmap as a binary 16-bit PGM image.
+    pub fn to_pgm(&self) -> Vec<u8> {
+        let mut bytes = format!("P5\n{} {}\n65535\n", self.width, self.height).into_bytes();
+        for pixel in &self.pixels {
+            bytes.extend(pixel.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Decode a binary 16-bit PGM image.
+    pub fn from_pgm(bytes: &[u8]) -> io::Result<Self> {
+        let (width, height, max_value, data) = read_pnm_header(bytes, *b"P5")?;
+        if max_value < 256 {
+            let message = "Unsupported PGM format: heightmaps must be 16-bit";
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        let data = pixel_data(data, width * height * 2)?;
+        let pixels = data
+            .chunks_exact(2)
+            .map(|pixel| u16::from_be_bytes([pixel[0], pixel[1]]))
+            .collect();
+        Ok(Self { width, height, pixels })
+    }
+
+    /// Encode the heightmap as raw little-endian 16-bit pixels, without a header.
+    pub fn to_raw(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(|pixel| pixel.to_le_bytes()).collect()
+    }
+
+    /// Decode raw little-endian 16-bit pixels of an image with the given width.
+    pub fn from_raw(bytes: &[u8], width: usize) -> io::Result<Self> {
+        let height = raw_height(bytes.len(), width * 2)?;
+        let pixels = bytes
+            .chunks_exact(2)
+            .map(|pixel| u16::from_le_bytes([pixel[0], pixel[1]]))
+            .collect();
+        Ok(Self { width, height, pixels })
+    }
+}
+
+impl VertexColorMap {
+    /// Encode the colors as a binary 8-bit PPM image.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        bytes.extend(self.pixels.as_flattened());
+        bytes
+    }
+
+    /// Decode a binary 8-bit PPM image.
+    pub fn from_ppm(bytes: &[u8]) -> io::Result<Self> {
+        let (width, height, max_value, data) = read_pnm_header(bytes, *b"P6")?;
+        if max_value != 255 {
+            let message = "Unsupported PPM format: vertex colors must be 8-bit";
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        let data = pixel_data(data, width * height * 3)?;
+        let pixels = data.chunks_exact(3).map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect();
+        Ok(Self { width, height, pixels })
+    }
+
+    /// Encode the colors as raw 8-bit RGB pixels, without a header.
+    pub fn to_raw(&self) -> Vec<u8> {
+        self.pixels.as_flattened().to_vec()
+    }
+
+    /// Decode raw 8-bit RGB pixels of an image with the given width.
+    pub fn from_raw(bytes: &[u8], width: usize) -> io::Result<Self> {
+        let height = raw_height(bytes.len(), width * 3)?;
+        let pixels = bytes.chunks_exact(3).map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect();
+        Ok(Self { width, height, pixels })
+    }
+}
+
+/// The size in pixels of an image of the landscapes from grid `min` to grid `max`.
+#[allow(clippy::cast_sign_loss)]
+fn image_size(min: (i32, i32), max: (i32, i32)) -> (usize, usize) {
+    let columns = (max.0 - min.0 + 1).max(0) as usize;
+    let rows = (max.1 - min.1 + 1).max(0) as usize;
+    (columns * STEPS + 1, rows * STEPS + 1)
+}
+
+fn check_image_size(width: usize, height: usize, len: usize) -> io::Result<()> {
+    let is_valid = |size: usize| size > STEPS && size % STEPS == 1;
+    if !is_valid(width) || !is_valid(height) || width * height != len {
+        let message = format!("Invalid image size: {width}x{height} (must be a multiple of 64 plus 1)");
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+    }
+    Ok(())
+}
+
+/// An editor for the plugin's landscapes, with all landscapes covered by the image created.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn editor_for_image(plugin: &Plugin, min: (i32, i32), width: usize, height: usize) -> TerrainEditor {
+    let mut editor = TerrainEditor::new(plugin);
+    let (columns, rows) = ((width / STEPS) as i32, (height / STEPS) as i32);
+    for y in min.1..min.1 + rows {
+        for x in min.0..min.0 + columns {
+            editor.insert((x, y));
+        }
+    }
+    editor
+}
+
+/// The world vertices of the pixels of an image, with its south west corner at grid `min`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn pixel_vertices(min: (i32, i32), width: usize, height: usize) -> impl Iterator<Item = (i32, i32)> {
+    let (x0, y0) = (min.0 * STEPS as i32, min.1 * STEPS as i32);
+    (0..height)
+        .rev()
+        .flat_map(move |row| (0..width).map(move |col| (x0 + col as i32, y0 + row as i32)))
+}
+
+/// Parse the header of a binary PNM image, returning its width, height, max value, and pixels.
+fn read_pnm_header(bytes: &[u8], magic: [u8; 2]) -> io::Result<(usize, usize, usize, &[u8])> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid PNM header");
+
+    if !bytes.starts_with(&magic) {
+        return Err(invalid());
+    }
+
+    let mut fields = [0; 3];
+    let mut i = magic.len();
+    for field in &mut fields {
+        // Skip whitespace and comments.
+        loop {
+            match bytes.get(i) {
+                Some(b'#') => {
+                    while bytes.get(i).is_some_and(|&b| b != b'\n') {
+                        i += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => i += 1,
+                _ => break,
+            }
+        }
+        let start = i;
+        while bytes.get(i).is_some_and(u8::is_ascii_digit) {
+            i += 1;
+        }
+        *field = std::str::from_utf8(&bytes[start..i])
+            .ok()
+            .and_then(|text| text.parse().ok())
+            .ok_or_else(invalid)?;
+    }
+
+    // A single whitespace character separates the header from the pixels.
+    if !bytes.get(i).is_some_and(u8::is_ascii_whitespace) {
+        return Err(invalid());
+    }
+
+    let [width, height, max_value] = fields;
+    Ok((width, height, max_value, &bytes[i + 1..]))
+}
+
+fn pixel_data(data: &[u8], len: usize) -> io::Result<&[u8]> {
+    data.get(..len)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Missing image pixels"))
+}
+
+// `usize::is_multiple_of` is newer than the minimum supported Rust version.
+#[allow(clippy::manual_is_multiple_of)]
+fn raw_height(len: usize, row_len: usize) -> io::Result<usize> {
+    if row_len == 0 || len % row_len != 0 {
+        let message = "Invalid raw image size: not a whole number of rows";
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    }
+    Ok(len / row_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn heightmap() {
+        // Heights rise towards the north east, by 8 units per vertex.
+        let pixels = (0..65u16)
+            .rev()
+            .flat_map(|row| (0..129u16).map(move |col| 32768 + row + col))
+            .collect();
+        let heightmap = Heightmap {
+            width: 129,
+            height: 65,
+            pixels,
+        };
+        assert_eq!(Heightmap::from_pgm(&heightmap.to_pgm()).unwrap(), heightmap);
+        assert_eq!(Heightmap::from_raw(&heightmap.to_raw(), 129).unwrap(), heightmap);
+
+        let mut plugin = Plugin::new();
+        let clamped = plugin.import_heightmap((0, 0), &heightmap).unwrap();
+        assert!(clamped.is_empty());
+        assert_eq!(plugin.objects_of_type::<Landscape>().count(), 2);
+        assert_eq!(plugin.export_heightmap((0, 0), (1, 0)), heightmap);
+
+        let landscape = plugin.objects_of_type::<Landscape>().nth(1).unwrap();
+        assert_eq!(landscape.grid, (1, 0));
+        let heights = landscape.decode_vertex_heights();
+        assert_eq!(heights[0][0].to_bits(), 512f32.to_bits());
+        assert_eq!(heights[64][64].to_bits(), 1536f32.to_bits());
+        assert!(landscape.vertex_normals.data[32][32][2] > 0);
+
+        // Missing landscapes are exported at the default height.
+        let exported = plugin.export_heightmap((0, -1), (0, -1));
+        assert_eq!(exported.pixels[0], 32768);
+        assert_eq!(exported.pixels[65], Heightmap::encode_height(TerrainEditor::DEFAULT_HEIGHT));
+
+        let invalid = Heightmap {
+            width: 64,
+            height: 64,
+            pixels: vec![0; 64 * 64],
+        };
+        assert!(plugin.import_heightmap((0, 0), &invalid).is_err());
+    }
+
+    #[test]
+    fn vertex_colors() {
+        let mut plugin = Plugin::new();
+        let mut colors = plugin.export_vertex_colors((-1, -1), (-1, -1));
+        colors.pixels[0] = [255, 0, 0];
+        plugin.import_vertex_colors((-1, -1), &colors).unwrap();
+
+        // The first pixel is the north west corner.
+        let landscape = plugin.objects_of_type::<Landscape>().next().unwrap();
+        assert_eq!(landscape.grid, (-1, -1));
+        assert_eq!(landscape.vertex_colors.data[64][0], [255, 0, 0]);
+        assert_eq!(plugin.export_vertex_colors((-1, -1), (-1, -1)), colors);
+
+        let ppm = b"P6\n# comment\n65 65\n255\n";
+        let mut bytes = ppm.to_vec();
+        bytes.extend(colors.to_raw());
+        assert_eq!(VertexColorMap::from_ppm(&bytes).unwrap(), colors);
+        assert_eq!(VertexColorMap::from_ppm(&colors.to_ppm()).unwrap(), colors);
+        assert!(VertexColorMap::from_ppm(&bytes[..100]).is_err());
+    }
+}
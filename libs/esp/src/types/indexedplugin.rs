@@ -0,0 +1,277 @@
+// rust std imports
+use std::ops::{Deref, DerefMut};
+
+// internal imports
+use crate::prelude::*;
+
+/// A plugin with an index of its objects' editor ids, for constant time lookups.
+///
+/// Editor ids are matched ignoring case, as in [`EditorId::editor_id_ascii_lowercase`]. All
+/// modifications go through the methods of this type so that the index stays consistent, the
+/// wrapped plugin can be read through [`Deref`] or taken back with [`IndexedPlugin::into_inner`].
+///
+/// ```
+/// use esp::{IndexedPlugin, Npc, Plugin};
+///
+/// let mut plugin = IndexedPlugin::new(Plugin::new());
+/// plugin.insert_or_replace(Npc { id: "fargoth".into(), ..Default::default() });
+/// plugin.get_mut::<Npc>("FARGOTH").unwrap().name = "Fargoth".into();
+/// assert_eq!(plugin.get::<Npc>("Fargoth").unwrap().name, "Fargoth");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct IndexedPlugin {
+    plugin: Plugin,
+    /// Maps lowercase editor ids to the positions of all objects with that id.
+    index: HashMap<String, Vec<usize>>,
+}
+
+/// Mutable access to an object of an [`IndexedPlugin`], see [`IndexedPlugin::get_mut`].
+///
+/// If the editor id of the object is changed, the index is updated when this is dropped.
+#[derive(Debug)]
+pub struct IndexedMut<'a, T> {
+    plugin: &'a mut IndexedPlugin,
+    position: usize,
+    /// The lowercase editor id of the object when it was borrowed.
+    original_id: String,
+    marker: std::marker::PhantomData<T>,
+}
+
+impl IndexedPlugin {
+    pub fn new(plugin: Plugin) -> Self {
+        let mut index: HashMap<String, Vec<usize>> = default();
+        for (position, object) in plugin.objects.iter().enumerate() {
+            let id = object.editor_id_ascii_lowercase().into_owned();
+            index.entry(id).or_default().push(position);
+        }
+        Self { plugin, index }
+    }
+
+    pub fn into_inner(self) -> Plugin {
+        self.plugin
+    }
+
+    /// The position of the object with the given tag and editor id.
+    pub fn position(&self, tag: &[u8; 4], id: &str) -> Option<usize> {
+        self.positions(id)
+            .iter()
+            .copied()
+            .find(|&position| self.plugin.objects[position].tag() == tag)
+    }
+
+    /// The object with the given tag and editor id.
+    pub fn get_object(&self, tag: &[u8; 4], id: &str) -> Option<&TES3Object> {
+        Some(&self.plugin.objects[self.position(tag, id)?])
+    }
+
+    /// The object of type `T` with the given editor id.
+    pub fn get<'a, T>(&'a self, id: &str) -> Option<&'a T>
+    where
+        &'a TES3Object: TryInto<&'a T>,
+    {
+        self.positions(id)
+            .iter()
+            .find_map(|&position| (&self.plugin.objects[position]).try_into().ok())
+    }
+
+    /// Mutable access to the object of type `T` with the given editor id.
+    pub fn get_mut<T>(&mut self, id: &str) -> Option<IndexedMut<'_, T>>
+    where
+        for<'a> &'a TES3Object: TryInto<&'a T>,
+    {
+        let position = self.position_of_type::<T>(id)?;
+        Some(IndexedMut {
+            original_id: self.plugin.objects[position].editor_id_ascii_lowercase().into_owned(),
+            plugin: self,
+            position,
+            marker: std::marker::PhantomData,
+        })
+    }
+
+    /// Insert an object, replacing any existing object with the same tag and editor id.
+    ///
+    /// New objects are appended to the end of the plugin. Returns the replaced object, if any.
+    pub fn insert_or_replace(&mut self, object: impl Into<TES3Object>) -> Option<TES3Object> {
+        let object = object.into();
+        let id = object.editor_id_ascii_lowercase().into_owned();
+        if let Some(position) = self.position(object.tag(), &id) {
+            return Some(std::mem::replace(&mut self.plugin.objects[position], object));
+        }
+        self.index.entry(id).or_default().push(self.plugin.objects.len());
+        self.plugin.objects.push(object);
+        None
+    }
+
+    /// Remove the object of type `T` with the given editor id.
+    ///
+    /// The positions of all following objects are shifted down by one.
+    pub fn remove<T>(&mut self, id: &str) -> Option<T>
+    where
+        TES3Object: TryInto<T>,
+        for<'a> &'a TES3Object: TryInto<&'a T>,
+    {
+        let position = self.position_of_type::<T>(id)?;
+        self.remove_at(position).try_into().ok()
+    }
+
+    /// Remove the object at the given position.
+    ///
+    /// The positions of all following objects are shifted down by one.
+    pub fn remove_at(&mut self, position: usize) -> TES3Object {
+        let object = self.plugin.objects.remove(position);
+        self.unindex(&object.editor_id_ascii_lowercase(), position);
+        for positions in self.index.values_mut() {
+            for other in positions.iter_mut().filter(|other| **other > position) {
+                *other -= 1;
+            }
+        }
+        object
+    }
+
+    fn position_of_type<T>(&self, id: &str) -> Option<usize>
+    where
+        for<'a> &'a TES3Object: TryInto<&'a T>,
+    {
+        self.positions(id)
+            .iter()
+            .copied()
+            .find(|&position| TryInto::<&T>::try_into(&self.plugin.objects[position]).is_ok())
+    }
+
+    fn positions(&self, id: &str) -> &[usize] {
+        self.index.get(&*id.cow_to_ascii_lowercase()).map_or(&[], Vec::as_slice)
+    }
+
+    fn unindex(&mut self, id: &str, position: usize) {
+        if let Some(positions) = self.index.get_mut(id) {
+            positions.retain(|&other| other != position);
+            if positions.is_empty() {
+                self.index.remove(id);
+            }
+        }
+    }
+}
+
+impl From<Plugin> for IndexedPlugin {
+    fn from(plugin: Plugin) -> Self {
+        Self::new(plugin)
+    }
+}
+
+impl From<IndexedPlugin> for Plugin {
+    fn from(plugin: IndexedPlugin) -> Self {
+        plugin.into_inner()
+    }
+}
+
+impl Deref for IndexedPlugin {
+    type Target = Plugin;
+
+    fn deref(&self) -> &Self::Target {
+        &self.plugin
+    }
+}
+
+impl<T> Deref for IndexedMut<'_, T>
+where
+    for<'a> &'a TES3Object: TryInto<&'a T>,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        let Ok(object) = (&self.plugin.plugin.objects[self.position]).try_into() else {
+            unreachable!();
+        };
+        object
+    }
+}
+
+impl<T> DerefMut for IndexedMut<'_, T>
+where
+    for<'a> &'a TES3Object: TryInto<&'a T>,
+    for<'a> &'a mut TES3Object: TryInto<&'a mut T>,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        let Ok(object) = (&mut self.plugin.plugin.objects[self.position]).try_into() else {
+            unreachable!();
+        };
+        object
+    }
+}
+
+impl<T> Drop for IndexedMut<'_, T> {
+    fn drop(&mut self) {
+        let object = &self.plugin.plugin.objects[self.position];
+        let id = object.editor_id_ascii_lowercase();
+        if id != self.original_id {
+            let id = id.into_owned();
+            self.plugin.unindex(&self.original_id, self.position);
+            self.plugin.index.entry(id).or_default().push(self.position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_plugin() -> IndexedPlugin {
+        let mut plugin = IndexedPlugin::default();
+        plugin.insert_or_replace(Header::default());
+        for id in ["Apple", "Banana"] {
+            plugin.insert_or_replace(Npc {
+                id: id.into(),
+                ..default()
+            });
+            plugin.insert_or_replace(MiscItem {
+                id: id.into(),
+                ..default()
+            });
+        }
+        plugin
+    }
+
+    #[test]
+    fn get() {
+        let plugin = test_plugin();
+        assert_eq!(plugin.get::<Npc>("APPLE").unwrap().id, "Apple");
+        assert_eq!(plugin.get::<MiscItem>("banana").unwrap().id, "Banana");
+        assert_eq!(plugin.position(MiscItem::TAG, "banana"), Some(4));
+        assert!(plugin.get::<Npc>("cherry").is_none());
+        assert!(plugin.get::<Cell>("apple").is_none());
+    }
+
+    #[test]
+    fn insert_or_replace() {
+        let mut plugin = test_plugin();
+        let old = plugin.insert_or_replace(Npc {
+            id: "APPLE".into(),
+            name: "Replaced".into(),
+            ..default()
+        });
+        assert_eq!(old.unwrap().editor_id(), "Apple");
+        assert_eq!(plugin.get::<Npc>("apple").unwrap().name, "Replaced");
+        assert_eq!(plugin.objects.len(), 5);
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut plugin = test_plugin();
+        plugin.get_mut::<Npc>("apple").unwrap().id = "Cherry".into();
+        assert!(plugin.get::<Npc>("apple").is_none());
+        assert_eq!(plugin.get::<Npc>("cherry").unwrap().id, "Cherry");
+        assert_eq!(plugin.get::<MiscItem>("apple").unwrap().id, "Apple");
+    }
+
+    #[test]
+    fn remove() {
+        let mut plugin = test_plugin();
+        let npc = plugin.remove::<Npc>("apple").unwrap();
+        assert_eq!(npc.id, "Apple");
+        assert!(plugin.get::<Npc>("apple").is_none());
+        assert_eq!(plugin.get::<MiscItem>("apple").unwrap().id, "Apple");
+        assert_eq!(plugin.get::<Npc>("banana").unwrap().id, "Banana");
+        assert_eq!(plugin.position(MiscItem::TAG, "banana"), Some(3));
+        assert!(plugin.remove::<Npc>("apple").is_none());
+    }
+}
@@ -11,6 +11,7 @@ pub struct Ingredient {
     pub mesh: String,
     pub icon: String,
     pub data: IngredientData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -56,7 +57,7 @@ impl Load for Ingredient {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
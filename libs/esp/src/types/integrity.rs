@@ -0,0 +1,416 @@
+// rust std imports
+use std::fmt::{self, Display};
+
+// internal imports
+use crate::prelude::*;
+
+/// Tags of records that can be carried in an inventory.
+const ITEMS: &[[u8; 4]] = &[
+    *b"ALCH", *b"APPA", *b"ARMO", *b"BOOK", *b"CLOT", *b"INGR", *b"LEVI", *b"LIGH", *b"LOCK", *b"MISC", *b"PROB", *b"REPA",
+    *b"WEAP",
+];
+
+/// Tags of records that can be placed in a cell.
+const PLACEABLE: &[[u8; 4]] = &[
+    *b"ACTI", *b"ALCH", *b"APPA", *b"ARMO", *b"BODY", *b"BOOK", *b"CLOT", *b"CONT", *b"CREA", *b"DOOR", *b"INGR", *b"LEVC",
+    *b"LEVI", *b"LIGH", *b"LOCK", *b"MISC", *b"NPC_", *b"PROB", *b"REPA", *b"STAT", *b"WEAP",
+];
+
+/// Tags of records that can act.
+const ACTORS: &[[u8; 4]] = &[*b"NPC_", *b"CREA"];
+
+/// Tags of records that can be spawned from leveled lists.
+const LEVELED_ACTORS: &[[u8; 4]] = &[*b"NPC_", *b"CREA", *b"LEVC"];
+
+const BODYPART: &[[u8; 4]] = &[*b"BODY"];
+const CLASS: &[[u8; 4]] = &[*b"CLAS"];
+const CREATURE: &[[u8; 4]] = &[*b"CREA"];
+const DIALOGUE: &[[u8; 4]] = &[*b"DIAL"];
+const ENCHANTING: &[[u8; 4]] = &[*b"ENCH"];
+const FACTION: &[[u8; 4]] = &[*b"FACT"];
+const GLOBAL: &[[u8; 4]] = &[*b"GLOB"];
+const LEVELED_CREATURE: &[[u8; 4]] = &[*b"LEVC"];
+const MISC_ITEM: &[[u8; 4]] = &[*b"MISC"];
+const NPC: &[[u8; 4]] = &[*b"NPC_"];
+const RACE: &[[u8; 4]] = &[*b"RACE"];
+const REGION: &[[u8; 4]] = &[*b"REGN"];
+const SCRIPT: &[[u8; 4]] = &[*b"SCPT"];
+const SOUND: &[[u8; 4]] = &[*b"SOUN"];
+const SPELL: &[[u8; 4]] = &[*b"SPEL"];
+const STATIC: &[[u8; 4]] = &[*b"STAT"];
+
+/// An id that does not refer to any record, see [`Plugin::check_references`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DanglingReference {
+    /// The tag of the referring record.
+    pub tag: [u8; 4],
+    /// The editor id of the referring record.
+    pub id: String,
+    /// The path of the referring field, e.g. `Npc.inventory[0]`.
+    pub path: String,
+    /// The id that could not be found.
+    pub target: String,
+    /// The tags of the record types that the id may refer to.
+    pub expected: &'static [[u8; 4]],
+}
+
+impl Plugin {
+    /// Find all ids in this plugin that do not refer to any record.
+    ///
+    /// Ids are resolved against the objects of this plugin and of the given `load_order`, which
+    /// should contain the masters of this plugin. Empty ids and the `player` id are always valid.
+    ///
+    /// This covers ids of scripts, spells, enchantments, sounds, inventories, leveled lists, AI
+    /// packages, cell references, and dialogue speakers and filters. Dialogue cell filters are not
+    /// checked, as they match cell names by prefix.
+    pub fn check_references(&self, load_order: &LoadOrder) -> Vec<DanglingReference> {
+        let mut checker = Checker {
+            load_order,
+            known: self.objects.iter().filter_map(ObjectKey::from_object).collect(),
+            tag: default(),
+            id: default(),
+            dangling: vec![],
+        };
+        for object in &self.objects {
+            checker.check_object(object);
+        }
+        checker.dangling
+    }
+}
+
+struct Checker<'a> {
+    load_order: &'a LoadOrder,
+    /// The keys of the objects of the checked plugin.
+    known: HashSet<ObjectKey>,
+    /// The tag of the record being checked.
+    tag: [u8; 4],
+    /// The editor id of the record being checked.
+    id: String,
+    dangling: Vec<DanglingReference>,
+}
+
+impl Checker<'_> {
+    fn check_object(&mut self, object: &TES3Object) {
+        self.tag = *object.tag();
+        self.id = object.editor_id().into_owned();
+
+        let name = object.type_name();
+        match object {
+            TES3Object::Npc(npc) => self.check_npc(name, npc),
+            TES3Object::Creature(creature) => self.check_creature(name, creature),
+            TES3Object::Container(container) => {
+                self.check(&format!("{name}.script"), &container.script, SCRIPT);
+                self.check_inventory(name, &container.inventory);
+            }
+            TES3Object::LeveledItem(leveled) => {
+                for (i, (id, _)) in leveled.items.iter().enumerate() {
+                    self.check(&format!("{name}.items[{i}]"), id, ITEMS);
+                }
+            }
+            TES3Object::LeveledCreature(leveled) => {
+                for (i, (id, _)) in leveled.creatures.iter().enumerate() {
+                    self.check(&format!("{name}.creatures[{i}]"), id, LEVELED_ACTORS);
+                }
+            }
+            TES3Object::Armor(Armor {
+                script,
+                enchanting,
+                biped_objects,
+                ..
+            })
+            | TES3Object::Clothing(Clothing {
+                script,
+                enchanting,
+                biped_objects,
+                ..
+            }) => {
+                self.check(&format!("{name}.script"), script, SCRIPT);
+                self.check(&format!("{name}.enchanting"), enchanting, ENCHANTING);
+                self.check_biped_objects(name, biped_objects);
+            }
+            TES3Object::Book(Book { script, enchanting, .. }) | TES3Object::Weapon(Weapon { script, enchanting, .. }) => {
+                self.check(&format!("{name}.script"), script, SCRIPT);
+                self.check(&format!("{name}.enchanting"), enchanting, ENCHANTING);
+            }
+            TES3Object::Activator(Activator { script, .. })
+            | TES3Object::Alchemy(Alchemy { script, .. })
+            | TES3Object::Apparatus(Apparatus { script, .. })
+            | TES3Object::Ingredient(Ingredient { script, .. })
+            | TES3Object::Lockpick(Lockpick { script, .. })
+            | TES3Object::MiscItem(MiscItem { script, .. })
+            | TES3Object::Probe(Probe { script, .. })
+            | TES3Object::RepairItem(RepairItem { script, .. })
+            | TES3Object::StartScript(StartScript { script, .. }) => {
+                self.check(&format!("{name}.script"), script, SCRIPT);
+            }
+            TES3Object::Door(door) => {
+                self.check(&format!("{name}.script"), &door.script, SCRIPT);
+                self.check(&format!("{name}.open_sound"), &door.open_sound, SOUND);
+                self.check(&format!("{name}.close_sound"), &door.close_sound, SOUND);
+            }
+            TES3Object::Light(light) => {
+                self.check(&format!("{name}.script"), &light.script, SCRIPT);
+                self.check(&format!("{name}.sound"), &light.sound, SOUND);
+            }
+            TES3Object::MagicEffect(effect) => self.check_magic_effect(name, effect),
+            TES3Object::Race(Race { spells, .. }) | TES3Object::Birthsign(Birthsign { spells, .. }) => {
+                self.check_spells(name, spells);
+            }
+            TES3Object::Faction(faction) => {
+                for (i, reaction) in faction.reactions.iter().enumerate() {
+                    self.check(&format!("{name}.reactions[{i}].faction"), &reaction.faction, FACTION);
+                }
+            }
+            TES3Object::SoundGen(sound_gen) => {
+                self.check(&format!("{name}.creature"), &sound_gen.creature, CREATURE);
+                self.check(&format!("{name}.sound"), &sound_gen.sound, SOUND);
+            }
+            TES3Object::Region(region) => {
+                self.check(&format!("{name}.sleep_creature"), &region.sleep_creature, LEVELED_CREATURE);
+                for (i, (id, _)) in region.sounds.iter().enumerate() {
+                    self.check(&format!("{name}.sounds[{i}]"), id, SOUND);
+                }
+            }
+            TES3Object::Bodypart(bodypart) => {
+                self.check(&format!("{name}.race"), &bodypart.race, RACE);
+            }
+            TES3Object::Cell(cell) => self.check_cell(name, cell),
+            TES3Object::DialogueInfo(info) => self.check_info(name, info),
+            _ => {}
+        }
+    }
+
+    fn check_npc(&mut self, name: &str, npc: &Npc) {
+        self.check_actor(name, &npc.script, &npc.inventory, &npc.spells, &npc.ai_packages);
+        self.check(&format!("{name}.race"), &npc.race, RACE);
+        self.check(&format!("{name}.class"), &npc.class, CLASS);
+        self.check(&format!("{name}.faction"), &npc.faction, FACTION);
+        self.check(&format!("{name}.head"), &npc.head, BODYPART);
+        self.check(&format!("{name}.hair"), &npc.hair, BODYPART);
+    }
+
+    fn check_creature(&mut self, name: &str, creature: &Creature) {
+        let Creature {
+            script,
+            inventory,
+            spells,
+            ai_packages,
+            ..
+        } = creature;
+        self.check_actor(name, script, inventory, spells, ai_packages);
+        self.check(&format!("{name}.sound"), &creature.sound, CREATURE);
+    }
+
+    fn check_magic_effect(&mut self, name: &str, effect: &MagicEffect) {
+        for (field, id) in [
+            ("bolt_sound", &effect.bolt_sound),
+            ("cast_sound", &effect.cast_sound),
+            ("hit_sound", &effect.hit_sound),
+            ("area_sound", &effect.area_sound),
+        ] {
+            self.check(&format!("{name}.{field}"), id, SOUND);
+        }
+        for (field, id) in [
+            ("cast_visual", &effect.cast_visual),
+            ("bolt_visual", &effect.bolt_visual),
+            ("hit_visual", &effect.hit_visual),
+            ("area_visual", &effect.area_visual),
+        ] {
+            self.check(&format!("{name}.{field}"), id, STATIC);
+        }
+    }
+
+    fn check_biped_objects(&mut self, name: &str, biped_objects: &[BipedObject]) {
+        for (i, biped_object) in biped_objects.iter().enumerate() {
+            let path = format!("{name}.biped_objects[{i}]");
+            self.check(&format!("{path}.male_bodypart"), &biped_object.male_bodypart, BODYPART);
+            self.check(&format!("{path}.female_bodypart"), &biped_object.female_bodypart, BODYPART);
+        }
+    }
+
+    fn check_cell(&mut self, name: &str, cell: &Cell) {
+        if let Some(region) = &cell.region {
+            self.check(&format!("{name}.region"), region, REGION);
+        }
+        let mut references: Vec<_> = cell.references.iter().collect();
+        references.sort_unstable_by_key(|(key, _)| **key);
+        for (key, reference) in references {
+            self.check_reference(&format!("{name}.references[{key:?}]"), reference);
+        }
+    }
+
+    fn check_info(&mut self, name: &str, info: &DialogueInfo) {
+        self.check(&format!("{name}.speaker_id"), &info.speaker_id, ACTORS);
+        self.check(&format!("{name}.speaker_race"), &info.speaker_race, RACE);
+        self.check(&format!("{name}.speaker_class"), &info.speaker_class, CLASS);
+        self.check(&format!("{name}.speaker_faction"), &info.speaker_faction, FACTION);
+        self.check(&format!("{name}.player_faction"), &info.player_faction, FACTION);
+        for (i, filter) in info.filters.iter().enumerate() {
+            let expected = match filter.filter_type {
+                FilterType::Global => GLOBAL,
+                FilterType::Journal => DIALOGUE,
+                FilterType::Item => ITEMS,
+                FilterType::Dead | FilterType::NotId => ACTORS,
+                FilterType::NotFaction => FACTION,
+                FilterType::NotClass => CLASS,
+                FilterType::NotRace => RACE,
+                _ => continue,
+            };
+            self.check(&format!("{name}.filters[{i}].id"), &filter.id, expected);
+        }
+    }
+
+    fn check_actor(
+        &mut self,
+        name: &str,
+        script: &str,
+        inventory: &[(i32, FixedString<32>)],
+        spells: &[String],
+        ai_packages: &[AiPackage],
+    ) {
+        self.check(&format!("{name}.script"), script, SCRIPT);
+        self.check_inventory(name, inventory);
+        self.check_spells(name, spells);
+        for (i, package) in ai_packages.iter().enumerate() {
+            let (target, expected) = match package {
+                AiPackage::Escort(package) => (&package.target, ACTORS),
+                AiPackage::Follow(package) => (&package.target, ACTORS),
+                AiPackage::Activate(package) => (&package.target, PLACEABLE),
+                _ => continue,
+            };
+            self.check(&format!("{name}.ai_packages[{i}].target"), target, expected);
+        }
+    }
+
+    fn check_inventory(&mut self, name: &str, inventory: &[(i32, FixedString<32>)]) {
+        for (i, (_, id)) in inventory.iter().enumerate() {
+            self.check(&format!("{name}.inventory[{i}]"), id, ITEMS);
+        }
+    }
+
+    fn check_spells(&mut self, name: &str, spells: &[String]) {
+        for (i, id) in spells.iter().enumerate() {
+            self.check(&format!("{name}.spells[{i}]"), id, SPELL);
+        }
+    }
+
+    fn check_reference(&mut self, path: &str, reference: &Reference) {
+        self.check(&format!("{path}.id"), &reference.id, PLACEABLE);
+        for (field, id, expected) in [
+            ("owner", &reference.owner, NPC),
+            ("owner_global", &reference.owner_global, GLOBAL),
+            ("owner_faction", &reference.owner_faction, FACTION),
+            ("key", &reference.key, MISC_ITEM),
+            ("trap", &reference.trap, SPELL),
+            ("soul", &reference.soul, CREATURE),
+        ] {
+            if let Some(id) = id {
+                self.check(&format!("{path}.{field}"), id, expected);
+            }
+        }
+    }
+
+    /// Record a dangling reference if `target` is not the id of a record with one of the
+    /// `expected` tags.
+    fn check(&mut self, path: &str, target: &str, expected: &'static [[u8; 4]]) {
+        if target.is_empty() || target.eq_ignore_ascii_case("player") {
+            return;
+        }
+        let exists = expected.iter().any(|tag| {
+            let key = ObjectKey::new(tag, target);
+            self.known.contains(&key) || self.load_order.contains(&key)
+        });
+        if !exists {
+            self.dangling.push(DanglingReference {
+                tag: self.tag,
+                id: self.id.clone(),
+                path: path.to_owned(),
+                target: target.to_owned(),
+                expected,
+            });
+        }
+    }
+}
+
+impl Display for DanglingReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expected: Vec<_> = self.expected.iter().map(|tag| tag.to_str_lossy()).collect();
+        write!(
+            f,
+            "{} {}: {} refers to missing \"{}\" (expected {})",
+            self.tag.to_str_lossy(),
+            self.id,
+            self.path,
+            self.target,
+            expected.join(" or ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_references() {
+        let master = Plugin {
+            objects: vec![
+                Race {
+                    id: "Dark Elf".into(),
+                    ..default()
+                }
+                .into(),
+                MiscItem {
+                    id: "key_test".into(),
+                    ..default()
+                }
+                .into(),
+            ],
+            ..default()
+        };
+        let load_order = LoadOrder::from_plugins(vec![("Morrowind.esm".into(), master)]);
+
+        let mut cell = Cell {
+            name: "Test Cell".into(),
+            ..default()
+        };
+        cell.references.insert(
+            (0, 1),
+            Reference {
+                id: "test_npc".into(),
+                key: Some("KEY_TEST".into()),
+                owner: Some("missing_owner".into()),
+                ..default()
+            },
+        );
+
+        let plugin = Plugin {
+            objects: vec![
+                Npc {
+                    id: "test_npc".into(),
+                    race: "dark elf".into(),
+                    class: "missing_class".into(),
+                    inventory: vec![(1, "key_test".to_owned().into()), (1, "missing_item".to_owned().into())],
+                    ..default()
+                }
+                .into(),
+                cell.into(),
+            ],
+            ..default()
+        };
+
+        let dangling: Vec<_> = plugin
+            .check_references(&load_order)
+            .into_iter()
+            .map(|entry| (entry.path, entry.target, entry.expected))
+            .collect();
+        assert_eq!(
+            dangling,
+            [
+                ("Npc.inventory[1]".into(), "missing_item".into(), ITEMS),
+                ("Npc.class".into(), "missing_class".into(), CLASS),
+                ("Cell.references[(0, 1)].owner".into(), "missing_owner".into(), NPC),
+            ]
+        );
+    }
+}
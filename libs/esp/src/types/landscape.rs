@@ -16,6 +16,7 @@ pub struct Landscape {
     pub world_map_data: WorldMapData,
     pub vertex_colors: VertexColors,
     pub texture_indices: TextureIndices,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -54,6 +55,17 @@ pub struct TextureIndices {
     pub data: Box<[[u16; 16]; 16]>,
 }
 
+/// A vertex whose height could not be encoded, see [`Landscape::encode_vertex_heights`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClampedVertex {
+    pub x: usize,
+    pub y: usize,
+    /// The requested height.
+    pub height: f32,
+    /// The height that was encoded instead.
+    pub encoded: f32,
+}
+
 impl Load for Landscape {
     fn load(stream: &mut Reader<'_>) -> io::Result<Self> {
         let mut this: Self = default();
@@ -96,7 +108,7 @@ impl Load for Landscape {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -230,6 +242,91 @@ impl Landscape {
         heights
     }
 
+    /// Encode absolute heights into the delta encoded `vertex_heights`, the inverse of
+    /// [`Landscape::decode_vertex_heights`].
+    ///
+    /// Heights are rounded to steps of 8 units, with each vertex stored as the difference to the
+    /// previous vertex of its row (or to the first vertex of the previous row). Differences beyond
+    /// the `i8` range are clamped, and the following vertices make up for the lost height as soon
+    /// as they can. Vertices whose rounded height could not be reached, i.e. that end up one or
+    /// more whole steps away from it, are returned.
+    ///
+    /// The offset is rounded as well, so that neighboring landscapes encode their shared border
+    /// vertices identically.
+    ///
+    /// This also recomputes the `vertex_normals` and `world_map_data` from the new heights.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn encode_vertex_heights(&mut self, heights: &[[f32; 65]; 65]) -> Vec<ClampedVertex> {
+        let offset = (heights[0][0] / 8.0).round();
+
+        let mut clamped = vec![];
+        let mut row_start = 0.0;
+
+        for (y, (row, deltas)) in heights.iter().zip(self.vertex_heights.data.iter_mut()).enumerate() {
+            let mut previous = row_start;
+            for (x, (&height, delta)) in row.iter().zip(deltas).enumerate() {
+                let target = (height / 8.0 - offset).round();
+                let clamped_delta = (target - previous).clamp(i8::MIN.into(), i8::MAX.into());
+                *delta = clamped_delta as i8;
+                previous += clamped_delta;
+                // Both values are whole steps, so any difference is at least one step.
+                if (target - previous).abs() > 0.5 {
+                    clamped.push(ClampedVertex {
+                        x,
+                        y,
+                        height,
+                        encoded: (previous + offset) * 8.0,
+                    });
+                }
+                if x == 0 {
+                    row_start = previous;
+                }
+            }
+        }
+
+        self.vertex_heights.offset = offset;
+        self.landscape_flags.insert(LandscapeFlags::USES_VERTEX_HEIGHTS_AND_NORMALS);
+
+        let heights = self.decode_vertex_heights();
+        self.update_vertex_normals(&heights);
+        self.update_world_map_data(&heights);
+
+        clamped
+    }
+
+    /// Recompute `vertex_normals` from the given (decoded) heights.
+    ///
+    /// Slopes are estimated from the neighboring vertices, edge vertices only use the neighbors
+    /// within this landscape.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    pub fn update_vertex_normals(&mut self, heights: &[[f32; 65]; 65]) {
+        for y in 0..65usize {
+            for x in 0..65usize {
+                let (x0, x1) = (x.saturating_sub(1), (x + 1).min(64));
+                let (y0, y1) = (y.saturating_sub(1), (y + 1).min(64));
+                let dx = (heights[y][x1] - heights[y][x0]) / ((x1 - x0) as f32 * 128.0);
+                let dy = (heights[y1][x] - heights[y0][x]) / ((y1 - y0) as f32 * 128.0);
+                let normal = Vec3::new(-dx, -dy, 1.0).normalize() * 127.0;
+                self.vertex_normals.data[y][x] = normal.round().to_array().map(|v| v as i8);
+            }
+        }
+    }
+
+    /// Recompute the low resolution `world_map_data` from the given (decoded) heights.
+    ///
+    /// Each of the 9x9 values is sampled from every 8th vertex, including the border vertices shared
+    /// with the neighboring landscapes, and scaled by 1/128 above sea level and by 1/16 below it.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn update_world_map_data(&mut self, heights: &[[f32; 65]; 65]) {
+        for row in 0..9 {
+            for col in 0..9 {
+                let height = heights[row * 8][col * 8];
+                let height = height / if height > 0.0 { 128.0 } else { 16.0 };
+                self.world_map_data.data[row][col] = height.clamp(i8::MIN.into(), i8::MAX.into()) as i8;
+            }
+        }
+    }
+
     #[allow(clippy::cast_precision_loss)]
     pub fn calculate_world_vertices(&self) -> Vec<Vec3> {
         const CELL_SIZE: f32 = 8192.0;
@@ -291,3 +388,98 @@ impl Landscape {
         triangles
     }
 }
+
+impl TextureIndices {
+    /// The raw texture index at column `x` and row `y` (from the south west corner).
+    ///
+    /// Indices are stored in blocks of 4x4 textures, with the blocks themselves in a 4x4 grid. A
+    /// value of `0` is the default texture, other values are the index of a [`LandscapeTexture`]
+    /// plus one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not less than 16.
+    pub fn get(&self, x: usize, y: usize) -> u16 {
+        let (block, offset) = block_offset(x, y);
+        self.data[block][offset]
+    }
+
+    /// Set the raw texture index at column `x` and row `y`, see [`TextureIndices::get`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not less than 16.
+    pub fn set(&mut self, x: usize, y: usize, value: u16) {
+        let (block, offset) = block_offset(x, y);
+        self.data[block][offset] = value;
+    }
+
+    /// Replace the [`LandscapeTexture`] indices found in `mapping` with their new indices.
+    ///
+    /// The default texture, and indices not found in `mapping`, are left unchanged.
+    pub fn remap(&mut self, mapping: &HashMap<u32, u32>) {
+        for value in self.data.as_flattened_mut() {
+            if let Some(index) = value.checked_sub(1).and_then(|index| mapping.get(&u32::from(index))) {
+                *value = (index + 1).try_into().unwrap_or(u16::MAX);
+            }
+        }
+    }
+}
+
+/// The position of a texture within `TextureIndices::data`.
+const fn block_offset(x: usize, y: usize) -> (usize, usize) {
+    assert!(x < 16 && y < 16, "texture position out of range");
+    ((y / 4) * 4 + x / 4, (y % 4) * 4 + x % 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn encode_vertex_heights() {
+        let mut heights: Box<[[f32; 65]; 65]> = zeroed_box();
+        for (y, row) in (0..).zip(heights.iter_mut()) {
+            for (x, height) in (0..).zip(row) {
+                *height = (96 + 24 * x - 40 * y) as f32;
+            }
+        }
+
+        let mut landscape = Landscape::default();
+        let clamped = landscape.encode_vertex_heights(&heights);
+        assert!(clamped.is_empty());
+        assert_eq!(landscape.decode_vertex_heights(), heights);
+
+        // Slopes along x are 3 steps, slopes along y are -5 steps.
+        let normal = landscape.vertex_normals.data[32][32];
+        assert!(normal[0] < 0 && normal[1] > 0 && normal[2] > 0);
+
+        assert_eq!(landscape.world_map_data.data[0][0], 0);
+        assert_eq!(landscape.vertex_heights.offset.to_bits(), 12.0f32.to_bits());
+        // The last row and column are sampled from the border vertices at 64.
+        assert_eq!(landscape.world_map_data.data[0][8], 12); // 1632 / 128
+        assert_eq!(landscape.world_map_data.data[8][8], -58); // -928 / 16
+    }
+
+    #[test]
+    fn encode_vertex_heights_clamped() {
+        let mut heights: Box<[[f32; 65]; 65]> = zeroed_box();
+        heights[0][1..].fill(1536.0); // 192 steps
+
+        let mut landscape = Landscape::default();
+        let clamped = landscape.encode_vertex_heights(&heights);
+        assert_eq!(
+            clamped,
+            [ClampedVertex {
+                x: 1,
+                y: 0,
+                height: 1536.0,
+                encoded: 1016.0,
+            }]
+        );
+
+        let decoded = landscape.decode_vertex_heights();
+        assert_eq!(decoded[0][1..4], [1016.0, 1536.0, 1536.0]);
+    }
+}
@@ -8,6 +8,7 @@ pub struct LandscapeTexture {
     pub id: String,
     pub index: u32,
     pub file_name: String,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 impl Load for LandscapeTexture {
@@ -34,7 +35,7 @@ impl Load for LandscapeTexture {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
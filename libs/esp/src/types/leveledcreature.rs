@@ -9,6 +9,7 @@ pub struct LeveledCreature {
     pub leveled_creature_flags: LeveledCreatureFlags,
     pub chance_none: u8,
     pub creatures: Vec<(String, u16)>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 impl Load for LeveledCreature {
@@ -48,7 +49,7 @@ impl Load for LeveledCreature {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -9,6 +9,7 @@ pub struct LeveledItem {
     pub leveled_item_flags: LeveledItemFlags,
     pub chance_none: u8,
     pub items: Vec<(String, u16)>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 impl Load for LeveledItem {
@@ -48,7 +49,7 @@ impl Load for LeveledItem {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -0,0 +1,166 @@
+// rust std imports
+use std::fmt;
+
+// internal imports
+use crate::prelude::*;
+
+/// Detailed context about a record that failed to load.
+///
+/// Plugin loading functions still return [`io::Error`], use [`LoadError::from_io_error`] to
+/// access these details.
+///
+/// ```no_run
+/// use esp::{LoadError, Plugin};
+///
+/// if let Err(error) = Plugin::from_path("broken.esp") {
+///     if let Some(details) = LoadError::from_io_error(&error) {
+///         println!("{} at offset {}", details.tag_str(), details.record_offset);
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct LoadError {
+    /// The tag of the record that failed to load.
+    pub tag: [u8; 4],
+    /// The position of the record within the plugin.
+    pub record_index: usize,
+    /// The byte offset of the record within the plugin.
+    pub record_offset: u64,
+    /// The tag of the subrecord that failed to load, if it could be determined.
+    pub subrecord_tag: Option<[u8; 4]>,
+    /// The byte offset of the subrecord within the plugin, if it could be determined.
+    pub subrecord_offset: Option<u64>,
+    /// The editor id of the record, if it was decoded before the failure.
+    pub editor_id: Option<String>,
+    /// The expected value, for failures originating from [`Reader::expect`].
+    pub expected: Option<String>,
+    /// The actual value, for failures originating from [`Reader::expect`].
+    pub actual: Option<String>,
+    /// The underlying error.
+    pub source: io::Error,
+}
+
+impl LoadError {
+    /// Build a `LoadError` for the record `bytes` that failed at relative position `error_position`.
+    fn new(
+        bytes: &[u8],
+        record_index: usize,
+        record_offset: u64,
+        error_position: u64,
+        encoding: &'static Encoding,
+        source: io::Error,
+    ) -> Self {
+        let tag = bytes.get(0..4).and_then(|tag| tag.try_into().ok()).unwrap_or_default();
+
+        // prefer the exact position of a failed `expect` over the position the reader stopped at
+        let unexpected_value = source.get_ref().and_then(|e| e.downcast_ref::<UnexpectedValue>());
+        let expected = unexpected_value.map(|value| value.expected.clone());
+        let actual = unexpected_value.map(|value| value.actual.clone());
+        let error_position = unexpected_value.map_or(error_position, |value| value.position);
+
+        let subrecord = subrecords(bytes).find(|(_, range)| range.contains(&error_position));
+
+        let editor_id = subrecords(bytes)
+            .filter(|(_, range)| range.end <= error_position)
+            .find_map(|(subrecord_tag, range)| {
+                #[allow(clippy::cast_possible_truncation)]
+                let range = range.start as usize..range.end as usize;
+                decode_editor_id(tag, subrecord_tag, &bytes[range], encoding)
+            });
+
+        Self {
+            tag,
+            record_index,
+            record_offset,
+            subrecord_tag: subrecord.as_ref().map(|(tag, _)| *tag),
+            subrecord_offset: subrecord.map(|(_, range)| record_offset + range.start),
+            editor_id,
+            expected,
+            actual,
+            source,
+        }
+    }
+
+    /// Get the `LoadError` details of an error returned by one of the plugin loading functions.
+    pub fn from_io_error(error: &io::Error) -> Option<&Self> {
+        error.get_ref().and_then(|inner| inner.downcast_ref())
+    }
+
+    pub fn tag_str(&self) -> &str {
+        std::str::from_utf8(&self.tag).unwrap_or("????")
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to load {} record #{}", self.tag_str(), self.record_index)?;
+        if let Some(editor_id) = &self.editor_id {
+            write!(f, " ({editor_id:?})")?;
+        }
+        write!(f, " at offset {}", self.record_offset)?;
+        if let (Some(tag), Some(offset)) = (&self.subrecord_tag, self.subrecord_offset) {
+            write!(f, ", subrecord {} at offset {offset}", tag.to_str_lossy())?;
+        }
+        write!(f, ": {}", self.source)
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<LoadError> for io::Error {
+    fn from(error: LoadError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+impl TES3Object {
+    /// Decode `bytes` as a single object, attaching [`LoadError`] details on failure.
+    pub(crate) fn load_with_context(
+        bytes: &[u8],
+        record_index: usize,
+        record_offset: u64,
+        encoding: &'static Encoding,
+    ) -> io::Result<Self> {
+        let mut stream = Reader::new(bytes);
+        stream.encoding = encoding;
+        stream.load().map_err(|error| {
+            let position = stream.cursor.position();
+            LoadError::new(bytes, record_index, record_offset, position, encoding, error).into()
+        })
+    }
+}
+
+/// Iterate over the tags and byte ranges of all subrecords within the record `bytes`.
+///
+/// Byte ranges are relative to the start of the record and include the subrecord headers.
+fn subrecords(bytes: &[u8]) -> impl Iterator<Item = ([u8; 4], std::ops::Range<u64>)> + '_ {
+    let mut stream = Reader::new(bytes);
+    stream.cursor.set_position(RawRecord::HEADER_SIZE as u64);
+    std::iter::from_fn(move || {
+        let start = stream.cursor.position();
+        let (tag, len) = stream.load::<([u8; 4], u32)>().ok()?;
+        let end = stream.skip(len).ok()?;
+        Some((tag, start..end))
+    })
+}
+
+/// Decode the editor id from a subrecord, if the subrecord is known to contain it.
+fn decode_editor_id(tag: [u8; 4], subrecord_tag: [u8; 4], bytes: &[u8], encoding: &'static Encoding) -> Option<String> {
+    let mut stream = Reader::new(bytes);
+    stream.encoding = encoding;
+    stream.skip(4).ok()?; // tag
+    match (&tag, &subrecord_tag) {
+        (Script::TAG, b"SCHD") => {
+            stream.skip(4).ok()?; // size
+            stream.load::<FixedString<32>>().ok().map(Into::into)
+        }
+        (DialogueInfo::TAG, b"INAM") => stream.load().ok(),
+        (Header::TAG | Skill::TAG | MagicEffect::TAG | Landscape::TAG | PathGrid::TAG | DialogueInfo::TAG, _) => None,
+        (_, b"NAME") => stream.load().ok(),
+        _ => None,
+    }
+}
@@ -0,0 +1,255 @@
+// rust std imports
+use std::path::Path;
+
+// internal imports
+use crate::prelude::*;
+
+/// A merged view over a list of plugins, as seen by the game.
+///
+/// Objects are identified by their [`ObjectKey`], when multiple plugins define the same object
+/// the last plugin in the load order wins.
+///
+/// ```no_run
+/// use esp::{LoadOrder, Npc};
+///
+/// let load_order = LoadOrder::from_paths(&["Morrowind.esm", "Tribunal.esm", "Bloodmoon.esm"])?;
+/// if let Some(npc) = load_order.get::<Npc>("fargoth") {
+///     println!("{npc:#?}");
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LoadOrder {
+    /// The file names and contents of the loaded plugins, in load order.
+    pub plugins: Vec<(String, Plugin)>,
+    /// Maps object keys to the (plugin index, object index) of all their definitions.
+    overrides: HashMap<ObjectKey, Vec<(usize, usize)>>,
+}
+
+/// Identifies an object across plugins.
+///
+/// This is the object's tag and lowercase editor id. Exterior cells, landscapes, and path grids are
+/// instead identified by their grid coordinates, as their editor ids depend on the (overridable)
+/// region.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ObjectKey {
+    pub tag: [u8; 4],
+    pub id: String,
+}
+
+impl ObjectKey {
+    pub fn new(tag: &[u8; 4], id: &str) -> Self {
+        Self {
+            tag: *tag,
+            id: id.to_ascii_lowercase(),
+        }
+    }
+
+    /// The key of the exterior cell at the given grid coordinates.
+    pub fn exterior_cell(grid: (i32, i32)) -> Self {
+        Self::with_grid(*Cell::TAG, grid)
+    }
+
+    /// The key of the landscape at the given grid coordinates.
+    pub fn landscape(grid: (i32, i32)) -> Self {
+        Self::with_grid(*Landscape::TAG, grid)
+    }
+
+    /// The key of the exterior path grid at the given grid coordinates.
+    pub fn path_grid(grid: (i32, i32)) -> Self {
+        Self::with_grid(*PathGrid::TAG, grid)
+    }
+
+    /// The key of the given object, or `None` for objects that cannot be overridden (e.g. headers).
+    pub fn from_object(object: &TES3Object) -> Option<Self> {
+        match object {
+            TES3Object::Header(_) | TES3Object::Unknown(_) => None,
+            TES3Object::Cell(cell) if cell.is_exterior() => Some(Self::exterior_cell(cell.data.grid)),
+            TES3Object::Landscape(landscape) => Some(Self::landscape(landscape.grid)),
+            // Interior path grids are always at (0, 0).
+            TES3Object::PathGrid(path_grid) if path_grid.data.grid != (0, 0) => Some(Self::path_grid(path_grid.data.grid)),
+            _ => Some(Self {
+                tag: *object.tag(),
+                id: object.editor_id_ascii_lowercase().into_owned(),
+            }),
+        }
+    }
+
+    fn with_grid(tag: [u8; 4], (x, y): (i32, i32)) -> Self {
+        Self {
+            tag,
+            id: format!("({x}, {y})"),
+        }
+    }
+}
+
+impl LoadOrder {
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// Load the plugins at the given paths, in parallel.
+    pub fn from_paths<P>(paths: &[P]) -> io::Result<Self>
+    where
+        P: AsRef<Path> + Sync,
+    {
+        let load = |path: &P| {
+            let path = path.as_ref();
+            let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            Ok((name, Plugin::from_path(path)?))
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let plugins = {
+            use rayon::prelude::*;
+            paths.par_iter().map(load).collect::<io::Result<_>>()?
+        };
+
+        // wasm32 architecture currently does not support rayon
+        #[cfg(target_arch = "wasm32")]
+        let plugins = paths.iter().map(load).collect::<io::Result<_>>()?;
+
+        Ok(Self::from_plugins(plugins))
+    }
+
+    /// Build a load order from the given file names and plugins, in load order.
+    pub fn from_plugins(plugins: Vec<(String, Plugin)>) -> Self {
+        let mut overrides: HashMap<ObjectKey, Vec<(usize, usize)>> = default();
+
+        for (i, (_, plugin)) in plugins.iter().enumerate() {
+            for (j, object) in plugin.objects.iter().enumerate() {
+                if let Some(key) = ObjectKey::from_object(object) {
+                    overrides.entry(key).or_default().push((i, j));
+                }
+            }
+        }
+
+        Self { plugins, overrides }
+    }
+
+    pub fn len(&self) -> usize {
+        self.plugins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// The position of the plugin with the given file name, ignoring case.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.plugins
+            .iter()
+            .position(|(plugin_name, _)| plugin_name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn contains(&self, key: &ObjectKey) -> bool {
+        self.overrides.contains_key(key)
+    }
+
+    /// The winning definition of the object with the given key.
+    pub fn get_object(&self, key: &ObjectKey) -> Option<&TES3Object> {
+        self.get_object_at(*self.overrides.get(key)?.last()?)
+    }
+
+    /// The winning definition of the object of type `T` with the given editor id.
+    pub fn get<'a, T>(&'a self, id: &str) -> Option<&'a T>
+    where
+        &'a TES3Object: TryInto<&'a T>,
+    {
+        let id = id.to_ascii_lowercase();
+        TES3Object::TAGS.iter().find_map(|tag| {
+            let key = ObjectKey {
+                tag: **tag,
+                id: id.clone(),
+            };
+            self.get_object(&key)?.try_into().ok()
+        })
+    }
+
+    /// All definitions of the object with the given key, in load order.
+    ///
+    /// Yields the file name of the defining plugin alongside each definition.
+    pub fn overrides(&self, key: &ObjectKey) -> impl Iterator<Item = (&str, &TES3Object)> {
+        self.overrides.get(key).into_iter().flatten().map(|&(i, j)| {
+            let (name, plugin) = &self.plugins[i];
+            (name.as_str(), &plugin.objects[j])
+        })
+    }
+
+    /// The winning definitions of all objects, in the order they were first defined.
+    pub fn objects(&self) -> impl Iterator<Item = &TES3Object> {
+        self.plugins.iter().enumerate().flat_map(move |(i, (_, plugin))| {
+            plugin.objects.iter().enumerate().filter_map(move |(j, object)| {
+                let entries = &self.overrides[&ObjectKey::from_object(object)?];
+                if entries[0] != (i, j) {
+                    return None;
+                }
+                self.get_object_at(*entries.last()?)
+            })
+        })
+    }
+
+    /// Map the master indices of the given plugin to load order indices.
+    ///
+    /// Load order indices start at 1 for the first plugin. Index 0 of the returned vector refers
+    /// to the plugin itself, masters missing from the load order are mapped to `None`.
+    pub fn master_indices(&self, plugin_index: usize) -> Vec<Option<u32>> {
+        let (_, plugin) = &self.plugins[plugin_index];
+        let masters = plugin.header().map_or(&[][..], |header| &header.masters);
+        std::iter::once(Some(plugin_index))
+            .chain(masters.iter().map(|(name, _)| self.position(name)))
+            .map(|index| index.and_then(|index| u32::try_from(index + 1).ok()))
+            .collect()
+    }
+
+    /// The references of the cell with the given key, merged across all plugins.
+    ///
+    /// References are keyed by `(mast_index, refr_index)`, where `mast_index` is the load order
+    /// index of the defining plugin (see [`LoadOrder::master_indices`]). Later plugins replace the
+    /// references of earlier plugins, including any deleted references. References of masters
+    /// missing from the load order are skipped.
+    ///
+    /// Moved references that a plugin stores without the reference itself (see
+    /// [`Cell::moved_references`]) are applied to the `moved_cell` of the master's reference.
+    pub fn cell_references(&self, key: &ObjectKey) -> HashMap<(u32, u32), Reference> {
+        self.cell_references_filtered(key, |_| true)
+    }
+
+    /// Like [`LoadOrder::cell_references`], but only merging plugins whose index passes `filter`.
+    pub(crate) fn cell_references_filtered(
+        &self,
+        key: &ObjectKey,
+        filter: impl Fn(usize) -> bool,
+    ) -> HashMap<(u32, u32), Reference> {
+        let mut references = HashMap::new();
+
+        for &(i, j) in self.overrides.get(key).into_iter().flatten().filter(|(i, _)| filter(*i)) {
+            let TES3Object::Cell(cell) = &self.plugins[i].1.objects[j] else {
+                continue;
+            };
+            let master_indices = self.master_indices(i);
+            for reference in cell.references.values() {
+                let Some(Some(mast_index)) = master_indices.get(reference.mast_index as usize) else {
+                    continue;
+                };
+                let mut reference = reference.clone();
+                reference.mast_index = *mast_index;
+                references.insert((reference.mast_index, reference.refr_index), reference);
+            }
+            for (&(mast_index, refr_index), &grid) in &cell.moved_references {
+                let Some(Some(mast_index)) = master_indices.get(mast_index as usize) else {
+                    continue;
+                };
+                if let Some(reference) = references.get_mut(&(*mast_index, refr_index)) {
+                    reference.moved_cell = Some(grid);
+                }
+            }
+        }
+
+        references
+    }
+
+    fn get_object_at(&self, (i, j): (usize, usize)) -> Option<&TES3Object> {
+        self.plugins.get(i)?.1.objects.get(j)
+    }
+}
@@ -18,6 +18,7 @@ pub struct MagicEffect {
     pub area_visual: String,
     pub description: String,
     pub data: MagicEffectData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -87,7 +88,7 @@ impl Load for MagicEffect {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -0,0 +1,569 @@
+// internal imports
+use crate::prelude::*;
+
+/// How [`Plugin::merge`] resolves records that are defined by more than one of the plugins.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum MergePolicy {
+    /// Records of later plugins replace those of earlier plugins, as in the game.
+    #[default]
+    LastWins,
+    /// Records of earlier plugins are kept.
+    FirstWins,
+    /// Differing definitions of the same record are an error.
+    Error,
+}
+
+impl Plugin {
+    /// Merge several plugins into a single plugin.
+    ///
+    /// The `plugins` are given as file names and plugins, in load order. Records are combined as
+    /// follows:
+    ///
+    /// - The master lists are combined, masters that are themselves being merged are dropped.
+    /// - Cell references are combined, references defined by the merged plugins are assigned new
+    ///   `refr_index` values, and the `mast_index` of all references is remapped.
+    /// - Landscape textures are renumbered, with the texture indices of landscapes rewritten to
+    ///   match.
+    /// - Dialogue infos are inserted into the combined topics according to their `prev_id` and
+    ///   `next_id`, with the links of their new neighbours updated.
+    /// - Any other records defined by more than one plugin are resolved according to `policy`.
+    pub fn merge(plugins: Vec<(String, Self)>, policy: MergePolicy) -> io::Result<Self> {
+        let masters = merged_masters(&plugins)?;
+        let master_indices = master_indices(&plugins, &masters);
+        let refr_indices = refr_indices(&plugins)?;
+        let texture_indices = texture_indices(&plugins);
+
+        let encoding = plugins.first().map_or(WINDOWS_1252, |(_, plugin)| plugin.encoding);
+
+        let mut header = plugins
+            .iter()
+            .find_map(|(_, plugin)| plugin.header())
+            .cloned()
+            .unwrap_or_default();
+        header.masters = masters;
+
+        let mut merger = Merger {
+            policy,
+            objects: vec![header.into()],
+            positions: default(),
+            topics: vec![],
+            topic_positions: default(),
+        };
+
+        for (i, (name, plugin)) in plugins.into_iter().enumerate() {
+            let remap_reference = |(mast_index, refr_index): (u32, u32)| -> io::Result<(u32, u32)> {
+                let Some(&(mast_index, source)) = master_indices[i].get(mast_index as usize) else {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{name}: invalid master index ({mast_index}, {refr_index})"),
+                    ));
+                };
+                let Some(source) = source else {
+                    return Ok((mast_index, refr_index));
+                };
+                match refr_indices[source].get(&refr_index) {
+                    Some(&refr_index) => Ok((0, refr_index)),
+                    None => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{name}: unresolved reference ({mast_index}, {refr_index})"),
+                    )),
+                }
+            };
+
+            let mut topic = None;
+
+            for object in plugin.objects {
+                match object {
+                    TES3Object::Header(_) => {}
+                    TES3Object::Dialogue(dialogue) => {
+                        topic = Some(merger.merge_dialogue(&name, dialogue)?);
+                    }
+                    TES3Object::DialogueInfo(info) => {
+                        let Some(topic) = topic else {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!("{name}: dialogue info '{}' has no dialogue", info.id),
+                            ));
+                        };
+                        merger.merge_info(&name, topic, info)?;
+                    }
+                    TES3Object::Cell(mut cell) => {
+                        cell.references = std::mem::take(&mut cell.references)
+                            .into_iter()
+                            .map(|(indices, mut reference)| {
+                                let indices = remap_reference(indices)?;
+                                (reference.mast_index, reference.refr_index) = indices;
+                                Ok((indices, reference))
+                            })
+                            .collect::<io::Result<_>>()?;
+                        cell.moved_references = std::mem::take(&mut cell.moved_references)
+                            .into_iter()
+                            .map(|(indices, grid)| Ok((remap_reference(indices)?, grid)))
+                            .collect::<io::Result<_>>()?;
+                        merger.merge_cell(&name, cell)?;
+                    }
+                    TES3Object::LandscapeTexture(mut texture) => {
+                        texture.index = texture_indices[i][&texture.index];
+                        merger.merge_object(&name, texture.into())?;
+                    }
+                    TES3Object::Landscape(mut landscape) => {
+                        landscape.texture_indices.remap(&texture_indices[i]);
+                        merger.merge_object(&name, landscape.into())?;
+                    }
+                    object => {
+                        topic = None;
+                        merger.merge_object(&name, object)?;
+                    }
+                }
+            }
+        }
+
+        let mut plugin = Self {
+            objects: merger.objects,
+            encoding,
+        };
+        for (dialogue, infos) in merger.topics {
+            plugin.objects.push(dialogue.into());
+            plugin.objects.extend(infos.into_iter().map(Into::into));
+        }
+        plugin.sort_objects();
+
+        Ok(plugin)
+    }
+}
+
+struct Merger {
+    policy: MergePolicy,
+    objects: Vec<TES3Object>,
+    positions: HashMap<ObjectKey, usize>,
+    topics: Vec<(Dialogue, Vec<DialogueInfo>)>,
+    topic_positions: HashMap<ObjectKey, usize>,
+}
+
+impl Merger {
+    /// Resolve two definitions of the same record, returning whether `new` should replace `old`.
+    fn resolve<T: PartialEq + EditorId + TypeInfo>(&self, name: &str, old: &T, new: &T) -> io::Result<bool> {
+        match self.policy {
+            MergePolicy::LastWins => Ok(true),
+            MergePolicy::FirstWins => Ok(false),
+            MergePolicy::Error if old == new => Ok(false),
+            MergePolicy::Error => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{name}: conflicting definitions of {} '{}'", new.tag_str(), new.editor_id()),
+            )),
+        }
+    }
+
+    fn merge_object(&mut self, name: &str, object: TES3Object) -> io::Result<()> {
+        let Some(key) = ObjectKey::from_object(&object) else {
+            self.objects.push(object);
+            return Ok(());
+        };
+        if let Some(&position) = self.positions.get(&key) {
+            if self.resolve(name, &self.objects[position], &object)? {
+                self.objects[position] = object;
+            }
+        } else {
+            self.positions.insert(key, self.objects.len());
+            self.objects.push(object);
+        }
+        Ok(())
+    }
+
+    fn merge_cell(&mut self, name: &str, mut cell: Cell) -> io::Result<()> {
+        let key = if cell.is_exterior() {
+            ObjectKey::exterior_cell(cell.data.grid)
+        } else {
+            ObjectKey::new(Cell::TAG, &cell.name)
+        };
+        let Some(&position) = self.positions.get(&key) else {
+            return self.merge_object(name, cell.into());
+        };
+
+        let references = std::mem::take(&mut cell.references);
+        let moved_references = std::mem::take(&mut cell.moved_references);
+
+        let TES3Object::Cell(existing) = &self.objects[position] else {
+            unreachable!();
+        };
+        let mut existing_header = existing.clone();
+        existing_header.references.clear();
+        existing_header.moved_references.clear();
+        if self.resolve(name, &existing_header, &cell)? {
+            let TES3Object::Cell(existing) = &mut self.objects[position] else {
+                unreachable!();
+            };
+            cell.references = std::mem::take(&mut existing.references);
+            cell.moved_references = std::mem::take(&mut existing.moved_references);
+            *existing = cell;
+        }
+
+        for (indices, reference) in references {
+            let TES3Object::Cell(existing) = &self.objects[position] else {
+                unreachable!();
+            };
+            let replace = match existing.references.get(&indices) {
+                Some(old) => self.resolve_reference(name, existing, old, &reference)?,
+                None => true,
+            };
+            if replace {
+                let TES3Object::Cell(existing) = &mut self.objects[position] else {
+                    unreachable!();
+                };
+                existing.references.insert(indices, reference);
+            }
+        }
+
+        // Moves of references that are now part of the cell are applied to them.
+        let TES3Object::Cell(existing) = &mut self.objects[position] else {
+            unreachable!();
+        };
+        existing.moved_references.extend(moved_references);
+        existing.moved_references.retain(|indices, grid| {
+            let Some(reference) = existing.references.get_mut(indices) else {
+                return true;
+            };
+            reference.moved_cell = Some(*grid);
+            false
+        });
+
+        Ok(())
+    }
+
+    fn resolve_reference(&self, name: &str, cell: &Cell, old: &Reference, new: &Reference) -> io::Result<bool> {
+        match self.policy {
+            MergePolicy::Error if old != new => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{name}: conflicting definitions of reference ({}, {}) in cell '{}'",
+                    new.mast_index,
+                    new.refr_index,
+                    cell.editor_id()
+                ),
+            )),
+            MergePolicy::LastWins => Ok(true),
+            _ => Ok(false),
+        }
+    }
+
+    fn merge_dialogue(&mut self, name: &str, dialogue: Dialogue) -> io::Result<usize> {
+        let key = ObjectKey::new(Dialogue::TAG, &dialogue.id);
+        let Some(&position) = self.topic_positions.get(&key) else {
+            self.topic_positions.insert(key, self.topics.len());
+            self.topics.push((dialogue, vec![]));
+            return Ok(self.topics.len() - 1);
+        };
+
+        if self.resolve(name, &self.topics[position].0, &dialogue)? {
+            self.topics[position].0 = dialogue;
+        }
+
+        Ok(position)
+    }
+
+    fn merge_info(&mut self, name: &str, topic: usize, mut info: DialogueInfo) -> io::Result<()> {
+        let infos = &self.topics[topic].1;
+
+        if let Some(position) = infos.iter().position(|other| other.id == info.id) {
+            if self.resolve(name, &infos[position], &info)? {
+                self.topics[topic].1[position] = info;
+            }
+            return Ok(());
+        }
+
+        let infos = &mut self.topics[topic].1;
+
+        if let Some(prev) = infos.iter().position(|other| other.id == info.prev_id) {
+            // Insert after the previous info, taking over its old next info.
+            let position = prev + 1;
+            if let Some(next) = infos.get_mut(position).filter(|next| next.prev_id == info.prev_id) {
+                next.prev_id.clone_from(&info.id);
+                info.next_id.clone_from(&next.id);
+            }
+            infos[prev].next_id.clone_from(&info.id);
+            infos.insert(position, info);
+        } else if let Some(next) = infos.iter().position(|other| other.id == info.next_id) {
+            // Insert before the next info, taking over its old previous info.
+            if let Some(prev) = next.checked_sub(1).and_then(|i| infos.get_mut(i)) {
+                if prev.next_id == info.next_id {
+                    prev.next_id.clone_from(&info.id);
+                    info.prev_id.clone_from(&prev.id);
+                }
+            }
+            infos[next].prev_id.clone_from(&info.id);
+            infos.insert(next, info);
+        } else {
+            infos.push(info);
+        }
+
+        Ok(())
+    }
+}
+
+/// Combine the master lists of `plugins`, skipping any masters that are part of `plugins`.
+fn merged_masters(plugins: &[(String, Plugin)]) -> io::Result<Vec<(String, u64)>> {
+    let mut masters: Vec<(String, u64)> = vec![];
+    for (_, plugin) in plugins {
+        for (name, size) in plugin.masters() {
+            let is_merged = plugins.iter().any(|(other, _)| other.eq_ignore_ascii_case(name));
+            let is_known = masters.iter().any(|(other, _)| other.eq_ignore_ascii_case(name));
+            if !is_merged && !is_known {
+                masters.push((name.clone(), *size));
+            }
+        }
+    }
+    if masters.len() > 0xFF {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Too many masters (limit: 255)"));
+    }
+    Ok(masters)
+}
+
+/// Map the master indices of each plugin to their merged master index.
+///
+/// Masters that are part of `plugins` are mapped to `0` along with the index of that plugin.
+fn master_indices(plugins: &[(String, Plugin)], masters: &[(String, u64)]) -> Vec<Vec<(u32, Option<usize>)>> {
+    let position = |name: &str| -> (u32, Option<usize>) {
+        if let Some(i) = plugins.iter().position(|(other, _)| other.eq_ignore_ascii_case(name)) {
+            return (0, Some(i));
+        }
+        let i = masters.iter().position(|(other, _)| other.eq_ignore_ascii_case(name));
+        #[allow(clippy::cast_possible_truncation)]
+        (i.map_or(0, |i| i as u32 + 1), None)
+    };
+    plugins
+        .iter()
+        .enumerate()
+        .map(|(i, (_, plugin))| {
+            std::iter::once((0, Some(i)))
+                .chain(plugin.masters().iter().map(|(name, _)| position(name)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Assign new `refr_index` values for the references defined by each plugin.
+fn refr_indices(plugins: &[(String, Plugin)]) -> io::Result<Vec<HashMap<u32, u32>>> {
+    let mut next_index = 1;
+    plugins
+        .iter()
+        .map(|(name, plugin)| {
+            let mut indices: Vec<_> = plugin
+                .objects_of_type::<Cell>()
+                .flat_map(|cell| cell.references.keys())
+                .filter_map(|&(mast_index, refr_index)| (mast_index == 0).then_some(refr_index))
+                .collect();
+            indices.sort_unstable();
+            indices.dedup();
+            indices
+                .into_iter()
+                .map(|refr_index| {
+                    if next_index > 0xFF_FFFF {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("{name}: too many references (limit: 16777215)"),
+                        ));
+                    }
+                    next_index += 1;
+                    Ok((refr_index, next_index - 1))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Assign new indices to the landscape textures of each plugin, shared by textures of equal id.
+fn texture_indices(plugins: &[(String, Plugin)]) -> Vec<HashMap<u32, u32>> {
+    let mut ids: HashMap<String, u32> = default();
+    plugins
+        .iter()
+        .map(|(_, plugin)| {
+            plugin
+                .objects_of_type::<LandscapeTexture>()
+                .map(|texture| {
+                    let next_index = ids.len().try_into().unwrap_or(u32::MAX);
+                    let index = *ids.entry(texture.id.to_ascii_lowercase()).or_insert(next_index);
+                    (texture.index, index)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_plugin(masters: &[&str], objects: Vec<TES3Object>) -> Plugin {
+        let header = Header {
+            masters: masters.iter().map(|name| ((*name).into(), 0)).collect(),
+            ..default()
+        };
+        Plugin {
+            objects: std::iter::once(header.into()).chain(objects).collect(),
+            ..default()
+        }
+    }
+
+    fn test_cell(references: &[((u32, u32), &str)]) -> TES3Object {
+        let mut cell = Cell {
+            name: "test_cell".into(),
+            ..default()
+        };
+        for &((mast_index, refr_index), id) in references {
+            let reference = Reference {
+                id: id.into(),
+                mast_index,
+                refr_index,
+                ..default()
+            };
+            cell.references.insert((mast_index, refr_index), reference);
+        }
+        cell.into()
+    }
+
+    fn test_info(id: &str, prev_id: &str, next_id: &str) -> TES3Object {
+        DialogueInfo {
+            id: id.into(),
+            prev_id: prev_id.into(),
+            next_id: next_id.into(),
+            ..default()
+        }
+        .into()
+    }
+
+    fn test_npc(name: &str) -> TES3Object {
+        Npc {
+            id: "test_npc".into(),
+            name: name.into(),
+            ..default()
+        }
+        .into()
+    }
+
+    #[test]
+    fn merge_references() {
+        let a = test_plugin(
+            &["Morrowind.esm"],
+            vec![test_cell(&[((0, 5), "a_new"), ((1, 7), "morrowind_ref")])],
+        );
+        let b = test_plugin(
+            &["Morrowind.esm", "Tribunal.esm", "a.esp"],
+            vec![test_cell(&[
+                ((0, 5), "b_new"),
+                ((2, 9), "tribunal_ref"),
+                ((3, 5), "a_new_moved"),
+            ])],
+        );
+        let merged = Plugin::merge(vec![("a.esp".into(), a), ("b.esp".into(), b)], default()).unwrap();
+
+        let masters: Vec<_> = merged.masters().iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(masters, ["Morrowind.esm", "Tribunal.esm"]);
+
+        let cell = merged.objects_of_type::<Cell>().next().unwrap();
+        let mut references: Vec<_> = cell
+            .references
+            .iter()
+            .map(|(&key, reference)| {
+                assert_eq!(key, (reference.mast_index, reference.refr_index));
+                (key, reference.id.as_str())
+            })
+            .collect();
+        references.sort_unstable();
+        assert_eq!(
+            references,
+            [
+                ((0, 1), "a_new_moved"),
+                ((0, 2), "b_new"),
+                ((1, 7), "morrowind_ref"),
+                ((2, 9), "tribunal_ref")
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_moved_references() {
+        let a = test_plugin(&["Morrowind.esm"], vec![test_cell(&[((0, 5), "a_new")])]);
+        let TES3Object::Cell(mut cell) = test_cell(&[]) else {
+            unreachable!();
+        };
+        cell.moved_references.insert((1, 7), (1, 2));
+        cell.moved_references.insert((2, 5), (3, 4));
+        let b = test_plugin(&["Morrowind.esm", "a.esp"], vec![cell.into()]);
+
+        let merged = Plugin::merge(vec![("a.esp".into(), a), ("b.esp".into(), b)], default()).unwrap();
+        let cell = merged.objects_of_type::<Cell>().next().unwrap();
+        assert_eq!(cell.references[&(0, 1)].moved_cell, Some((3, 4)));
+        assert_eq!(cell.moved_references.len(), 1);
+        assert_eq!(cell.moved_references[&(1, 7)], (1, 2));
+    }
+
+    #[test]
+    fn merge_landscape_textures() {
+        let texture = |id: &str, index| -> TES3Object {
+            LandscapeTexture {
+                id: id.into(),
+                index,
+                ..default()
+            }
+            .into()
+        };
+        let mut landscape = Landscape {
+            grid: (1, 2),
+            ..default()
+        };
+        landscape.texture_indices.data[0] = [0, 1, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+        let a = test_plugin(&[], vec![texture("grass", 0), texture("dirt", 1)]);
+        let b = test_plugin(&[], vec![texture("rock", 0), texture("Grass", 1), landscape.into()]);
+        let merged = Plugin::merge(vec![("a.esp".into(), a), ("b.esp".into(), b)], default()).unwrap();
+
+        let textures: Vec<_> = merged
+            .objects_of_type::<LandscapeTexture>()
+            .map(|texture| (texture.id.as_str(), texture.index))
+            .collect();
+        assert_eq!(textures, [("Grass", 0), ("dirt", 1), ("rock", 2)]);
+
+        let landscape = merged.objects_of_type::<Landscape>().next().unwrap();
+        assert_eq!(landscape.texture_indices.data[0][..4], [0, 3, 1, 3]);
+    }
+
+    #[test]
+    fn merge_dialogue() {
+        let dialogue = || -> TES3Object {
+            Dialogue {
+                id: "test_topic".into(),
+                ..default()
+            }
+            .into()
+        };
+        let a = test_plugin(&[], vec![dialogue(), test_info("1", "", "2"), test_info("2", "1", "")]);
+        let b = test_plugin(&[], vec![dialogue(), test_info("3", "1", "2"), test_info("4", "", "1")]);
+        let merged = Plugin::merge(vec![("a.esp".into(), a), ("b.esp".into(), b)], default()).unwrap();
+
+        assert_eq!(merged.objects_of_type::<Dialogue>().count(), 1);
+        let infos: Vec<_> = merged
+            .objects_of_type::<DialogueInfo>()
+            .map(|info| (info.prev_id.as_str(), info.id.as_str(), info.next_id.as_str()))
+            .collect();
+        assert_eq!(infos, [("", "4", "1"), ("4", "1", "3"), ("1", "3", "2"), ("3", "2", "")]);
+    }
+
+    #[test]
+    fn merge_policy() {
+        let plugins = || {
+            vec![
+                ("a.esp".into(), test_plugin(&[], vec![test_npc("A")])),
+                ("b.esp".into(), test_plugin(&[], vec![test_npc("B")])),
+            ]
+        };
+        let name = |plugin: Plugin| plugin.objects_of_type::<Npc>().next().unwrap().name.clone();
+
+        assert_eq!(name(Plugin::merge(plugins(), MergePolicy::LastWins).unwrap()), "B");
+        assert_eq!(name(Plugin::merge(plugins(), MergePolicy::FirstWins).unwrap()), "A");
+        assert!(Plugin::merge(plugins(), MergePolicy::Error).is_err());
+
+        let mut same = plugins();
+        same[1].1 = same[0].1.clone();
+        assert_eq!(name(Plugin::merge(same, MergePolicy::Error).unwrap()), "A");
+    }
+}
@@ -11,6 +11,7 @@ pub struct MiscItem {
     pub mesh: String,
     pub icon: String,
     pub data: MiscItemData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -54,7 +55,7 @@ impl Load for MiscItem {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -22,6 +22,7 @@ pub struct Npc {
     pub npc_flags: NpcFlags,
     pub blood_type: u8,
     pub data: NpcData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -129,7 +130,7 @@ impl Load for Npc {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -9,6 +9,7 @@ pub struct PathGrid {
     pub data: PathGridData,
     pub points: Vec<PathGridPoint>,
     pub connections: Vec<u32>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -56,7 +57,7 @@ impl Load for PathGrid {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -1,14 +1,20 @@
 // rust std imports
 use std::io::Write;
+use std::ops::Range;
 use std::path::Path;
 
 // internal imports
 use crate::prelude::*;
 
 #[esp_meta]
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, SmartDefault)]
 pub struct Plugin {
     pub objects: Vec<TES3Object>,
+    /// The encoding of all text in the plugin, used for both loading and saving.
+    #[default(WINDOWS_1252)]
+    #[cfg_attr(feature = "serde", serde(skip, default = "Plugin::default_encoding"))]
+    #[diff(skip)]
+    pub encoding: &'static Encoding,
 }
 
 impl Plugin {
@@ -57,19 +63,66 @@ impl Plugin {
     }
 
     pub fn load_bytes_filtered(&mut self, bytes: &[u8], filter: impl Fn([u8; 4]) -> bool) -> io::Result<()> {
-        let mut stream = Reader::new(bytes);
+        let encoding = self.encoding;
+        self.load_objects(bytes, filter, |bytes, index, offset| {
+            TES3Object::load_with_context(bytes, index, offset, encoding)
+        })
+    }
 
+    pub fn from_path_with_encoding(path: impl AsRef<Path>, encoding: &'static Encoding) -> io::Result<Self> {
+        let mut plugin = Self::new();
+        plugin.load_path_with_encoding(path, encoding)?;
+        Ok(plugin)
+    }
+
+    pub fn load_path_with_encoding(&mut self, path: impl AsRef<Path>, encoding: &'static Encoding) -> io::Result<()> {
+        self.load_bytes_with_encoding(&std::fs::read(path)?, encoding)
+    }
+
+    /// Load the given bytes, decoding text with `encoding`.
+    ///
+    /// The encoding is remembered and used again by [`Plugin::save_bytes`].
+    pub fn load_bytes_with_encoding(&mut self, bytes: &[u8], encoding: &'static Encoding) -> io::Result<()> {
+        self.encoding = encoding;
+        self.load_bytes(bytes)
+    }
+
+    /// Load the given bytes, decoding text with the encoding chosen by [`Plugin::detect_encoding`].
+    pub fn load_bytes_detect_encoding(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.load_bytes_with_encoding(bytes, Self::detect_encoding(bytes))
+    }
+
+    pub fn from_path_lenient(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut plugin = Self::new();
+        plugin.load_path_lenient(path)?;
+        Ok(plugin)
+    }
+
+    pub fn load_path_lenient(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.load_bytes_lenient(&std::fs::read(path)?)
+    }
+
+    /// Load the given bytes, preserving any records or subrecords that could not be decoded.
+    ///
+    /// Records of unknown types (and known records that fail to decode) are kept as
+    /// [`TES3Object::Unknown`]. Unknown subrecords of known records are kept in their
+    /// `unknown_subrecords` field. Both are written back unchanged by [`Plugin::save_bytes`].
+    pub fn load_bytes_lenient(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let encoding = self.encoding;
+        self.load_objects(bytes, |_| true, |bytes, _, _| Ok(TES3Object::load_lenient(bytes, encoding)))
+    }
+
+    pub(crate) fn load_objects<F>(&mut self, bytes: &[u8], filter: impl Fn([u8; 4]) -> bool, load: F) -> io::Result<()>
+    where
+        F: Fn(&[u8], usize, u64) -> io::Result<TES3Object> + Sync,
+    {
         // do a quick pass calculating the positions of objects
-        let mut offsets = Vec::new();
-        while let Ok((tag, len)) = stream.load::<([u8; 4], u32)>() {
-            let start = stream.cursor.position() - 8;
-            if let Ok(end) = stream.skip(len + 8) {
-                if filter(tag) {
-                    #[allow(clippy::cast_possible_truncation)]
-                    offsets.push(start as usize..end as usize);
-                }
-            }
-        }
+        // indices count every record, including the ones rejected by `filter`
+        let offsets: Vec<_> = Self::record_offsets(bytes, |_| true)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, range)| filter(bytes[range.start..][..4].try_into().unwrap_or_default()))
+            .collect();
 
         // now visit each chunk and decode them all in parellel
         #[cfg(not(target_arch = "wasm32"))]
@@ -77,7 +130,7 @@ impl Plugin {
             use rayon::prelude::*;
             self.objects = offsets
                 .into_par_iter()
-                .map(|range| Reader::new(&bytes[range]).load())
+                .map(|(i, range)| load(&bytes[range.clone()], i, range.start as u64))
                 .collect::<io::Result<_>>()?;
         }
 
@@ -86,15 +139,39 @@ impl Plugin {
         {
             self.objects = offsets
                 .into_iter()
-                .map(|range| Reader::new(&bytes[range]).load())
+                .map(|(i, range)| load(&bytes[range.clone()], i, range.start as u64))
                 .collect::<io::Result<_>>()?;
         }
 
         Ok(())
     }
 
+    /// Calculate the byte ranges of all records whose tag is accepted by `filter`.
+    pub(crate) fn record_offsets(bytes: &[u8], filter: impl Fn([u8; 4]) -> bool) -> Vec<Range<usize>> {
+        let mut stream = Reader::new(bytes);
+
+        let mut offsets = Vec::new();
+        while let Ok((tag, len)) = stream.load::<([u8; 4], u32)>() {
+            let start = stream.cursor.position() - 8;
+            if let Ok(end) = stream.skip(len + 8) {
+                if filter(tag) {
+                    #[allow(clippy::cast_possible_truncation)]
+                    offsets.push(start as usize..end as usize);
+                }
+            }
+        }
+
+        offsets
+    }
+
     pub fn save_bytes(&mut self) -> io::Result<Vec<u8>> {
+        self.save_bytes_with_encoding(self.encoding)
+    }
+
+    /// Save the plugin, encoding text with `encoding` rather than [`Plugin::encoding`].
+    pub fn save_bytes_with_encoding(&mut self, encoding: &'static Encoding) -> io::Result<Vec<u8>> {
         let mut stream = Writer::new(vec![]);
+        stream.encoding = encoding;
 
         // update header
         let num_objects = self.objects.len();
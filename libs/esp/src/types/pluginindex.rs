@@ -0,0 +1,198 @@
+// rust std imports
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+// internal imports
+use crate::prelude::*;
+
+/// A random-access index over the records of a plugin file.
+///
+/// Maps each record's (case-insensitive) editor id to its byte range within the file, allowing
+/// individual records to be decoded on demand without loading the whole plugin.
+///
+/// ```no_run
+/// use esp::{Npc, PluginIndex};
+///
+/// let index = PluginIndex::from_path("Morrowind.esm")?;
+/// if let Some(npc) = index.get::<Npc>("fargoth")? {
+///     println!("{npc:#?}");
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug, SmartDefault)]
+pub struct PluginIndex {
+    pub path: PathBuf,
+    /// The encoding used to decode text, see [`Plugin::encoding`].
+    #[default(WINDOWS_1252)]
+    pub encoding: &'static Encoding,
+    entries: HashMap<String, Vec<IndexEntry>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IndexEntry {
+    pub tag: [u8; 4],
+    pub range: Range<u64>,
+}
+
+impl PluginIndex {
+    pub fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_path_filtered(path, |_| true)
+    }
+
+    pub fn from_path_filtered(path: impl AsRef<Path>, filter: impl Fn([u8; 4]) -> bool) -> io::Result<Self> {
+        Self::from_path_with_encoding(path, filter, WINDOWS_1252)
+    }
+
+    pub fn from_path_with_encoding(
+        path: impl AsRef<Path>,
+        filter: impl Fn([u8; 4]) -> bool,
+        encoding: &'static Encoding,
+    ) -> io::Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        let mut index = Self::from_bytes_with_encoding(&bytes, filter, encoding)?;
+        index.path = path.to_path_buf();
+        Ok(index)
+    }
+
+    /// Build an index from the given bytes.
+    ///
+    /// Note that [`PluginIndex::path`] must be assigned before records can be read from the index.
+    pub fn from_bytes_filtered(bytes: &[u8], filter: impl Fn([u8; 4]) -> bool) -> io::Result<Self> {
+        Self::from_bytes_with_encoding(bytes, filter, WINDOWS_1252)
+    }
+
+    /// Build an index from the given bytes, decoding text with `encoding`.
+    ///
+    /// Note that [`PluginIndex::path`] must be assigned before records can be read from the index.
+    pub fn from_bytes_with_encoding(
+        bytes: &[u8],
+        filter: impl Fn([u8; 4]) -> bool,
+        encoding: &'static Encoding,
+    ) -> io::Result<Self> {
+        let offsets = Plugin::record_offsets(bytes, filter);
+
+        let ids = {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                use rayon::prelude::*;
+                offsets
+                    .par_iter()
+                    .map(|range| peek_editor_id(&bytes[range.clone()], encoding))
+                    .collect::<io::Result<Vec<_>>>()?
+            }
+            // wasm32 architecture currently does not support rayon
+            #[cfg(target_arch = "wasm32")]
+            {
+                offsets
+                    .iter()
+                    .map(|range| peek_editor_id(&bytes[range.clone()], encoding))
+                    .collect::<io::Result<Vec<_>>>()?
+            }
+        };
+
+        let mut entries: HashMap<String, Vec<IndexEntry>> = default();
+        for (range, id) in offsets.into_iter().zip(ids) {
+            let tag = bytes[range.start..range.start + 4].try_into().unwrap_or_default();
+            let range = range.start as u64..range.end as u64;
+            entries.entry(id).or_default().push(IndexEntry { tag, range });
+        }
+
+        Ok(Self {
+            path: default(),
+            encoding,
+            entries,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.entries.contains_key(&*id.cow_to_ascii_lowercase())
+    }
+
+    /// All index entries whose editor id matches `id`, ignoring case.
+    pub fn entries(&self, id: &str) -> &[IndexEntry] {
+        self.entries.get(&*id.cow_to_ascii_lowercase()).map_or(&[], Vec::as_slice)
+    }
+
+    /// Read and decode the record of type `T` with the given editor id.
+    pub fn get<T>(&self, id: &str) -> io::Result<Option<T>>
+    where
+        TES3Object: TryInto<T>,
+    {
+        for entry in self.entries(id) {
+            if let Ok(object) = self.read_object(entry)?.try_into() {
+                return Ok(Some(object));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Read and decode the record with the given tag and editor id.
+    pub fn get_object(&self, tag: &[u8; 4], id: &str) -> io::Result<Option<TES3Object>> {
+        self.entries(id)
+            .iter()
+            .find(|entry| &entry.tag == tag)
+            .map(|entry| self.read_object(entry))
+            .transpose()
+    }
+
+    pub fn read_object(&self, entry: &IndexEntry) -> io::Result<TES3Object> {
+        let bytes = self.read_bytes(entry)?;
+        let mut stream = Reader::new(&bytes);
+        stream.encoding = self.encoding;
+        stream.load()
+    }
+
+    pub fn read_bytes(&self, entry: &IndexEntry) -> io::Result<Vec<u8>> {
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(entry.range.start))?;
+
+        #[allow(clippy::cast_possible_truncation)]
+        let mut bytes = vec![0; (entry.range.end - entry.range.start) as usize];
+        file.read_exact(&mut bytes)?;
+
+        Ok(bytes)
+    }
+}
+
+/// Get the lowercase editor id of the given record bytes.
+///
+/// Most records store their id in a leading `NAME` subrecord, which can be read without needing
+/// to decode the rest of the record. Otherwise we fall back to decoding the full object.
+fn peek_editor_id(bytes: &[u8], encoding: &'static Encoding) -> io::Result<String> {
+    const DERIVED_IDS: [&[u8; 4]; 8] = [
+        Header::TAG,
+        Skill::TAG,
+        MagicEffect::TAG,
+        Script::TAG,
+        Cell::TAG,
+        Landscape::TAG,
+        PathGrid::TAG,
+        DialogueInfo::TAG,
+    ];
+
+    if !DERIVED_IDS.iter().any(|tag| bytes.starts_with(*tag)) {
+        let mut stream = Reader::new(&bytes[RawRecord::HEADER_SIZE..]);
+        stream.encoding = encoding;
+        if stream.expect(*b"NAME").is_ok() {
+            let mut id: String = stream.load()?;
+            id.make_ascii_lowercase();
+            return Ok(id);
+        }
+    }
+
+    let mut stream = Reader::new(bytes);
+    stream.encoding = encoding;
+    let object: TES3Object = stream.load()?;
+    Ok(object.editor_id_ascii_lowercase().into_owned())
+}
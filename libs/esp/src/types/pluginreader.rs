@@ -0,0 +1,211 @@
+// rust std imports
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+// internal imports
+use crate::prelude::*;
+
+/// A streaming reader that yields the records of a plugin one at a time.
+///
+/// Unlike [`Plugin::load_path`] the file is never read into memory as a whole, only a single record
+/// is buffered at any given time. Records that are rejected by a filter are skipped over without
+/// being read at all.
+///
+/// ```no_run
+/// use esp::{Npc, PluginReader};
+///
+/// let mut reader = PluginReader::from_path("Morrowind.esm")?;
+/// for object in reader.objects_filtered(|tag| &tag == Npc::TAG) {
+///     let npc: Npc = object?.try_into().unwrap();
+///     println!("{}", npc.id);
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct PluginReader<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    /// The index of the next record in the stream.
+    next_index: usize,
+    /// The byte offset of the next record in the stream.
+    next_offset: u64,
+    /// Whether to preserve records and subrecords that could not be decoded.
+    lenient: bool,
+    /// The encoding used to decode text.
+    encoding: &'static Encoding,
+}
+
+/// An undecoded record as yielded by [`PluginReader::next_raw_record`].
+///
+/// The `bytes` field contains the complete record, including its 16 byte header.
+#[derive(Clone, Debug, SmartDefault, Eq, PartialEq)]
+pub struct RawRecord {
+    pub tag: [u8; 4],
+    pub flags: ObjectFlags,
+    pub bytes: Vec<u8>,
+    /// The encoding used by [`RawRecord::decode`], taken from the reader.
+    #[default(WINDOWS_1252)]
+    pub encoding: &'static Encoding,
+}
+
+impl RawRecord {
+    pub const HEADER_SIZE: usize = 16;
+
+    pub fn data(&self) -> &[u8] {
+        &self.bytes[Self::HEADER_SIZE..]
+    }
+
+    pub fn decode(&self) -> io::Result<TES3Object> {
+        let mut stream = Reader::new(&self.bytes);
+        stream.encoding = self.encoding;
+        stream.load()
+    }
+}
+
+impl PluginReader<BufReader<File>> {
+    pub fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::from_reader(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: Read + Seek> PluginReader<R> {
+    pub fn from_reader(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: vec![],
+            next_index: 0,
+            next_offset: 0,
+            lenient: false,
+            encoding: WINDOWS_1252,
+        }
+    }
+
+    /// Enable or disable lenient mode, see [`Plugin::load_bytes_lenient`].
+    #[must_use]
+    pub const fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Set the encoding used to decode text, see [`Plugin::encoding`].
+    #[must_use]
+    pub const fn encoding(mut self, encoding: &'static Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Read the next record without decoding it.
+    ///
+    /// Returns `Ok(None)` once the end of the stream has been reached.
+    pub fn next_raw_record(&mut self) -> io::Result<Option<RawRecord>> {
+        self.next_raw_record_filtered(|_| true)
+    }
+
+    /// Read the next record accepted by `filter` without decoding it.
+    ///
+    /// Rejected records are skipped over without being read.
+    pub fn next_raw_record_filtered(&mut self, filter: impl Fn([u8; 4]) -> bool) -> io::Result<Option<RawRecord>> {
+        if self.fill_buffer(filter)?.is_none() {
+            return Ok(None);
+        }
+        let tag = self.buffer[0..4].try_into().unwrap_or_default();
+        let flags = ObjectFlags::from_bits_retain(u32::from_le_bytes(self.buffer[12..16].try_into().unwrap_or_default()));
+        Ok(Some(RawRecord {
+            tag,
+            flags,
+            bytes: self.buffer.clone(),
+            encoding: self.encoding,
+        }))
+    }
+
+    /// Read and decode the next object.
+    ///
+    /// Returns `Ok(None)` once the end of the stream has been reached.
+    pub fn next_object(&mut self) -> io::Result<Option<TES3Object>> {
+        self.next_object_filtered(|_| true)
+    }
+
+    /// Read and decode the next object accepted by `filter`.
+    ///
+    /// Rejected records are skipped over without being read or decoded.
+    pub fn next_object_filtered(&mut self, filter: impl Fn([u8; 4]) -> bool) -> io::Result<Option<TES3Object>> {
+        let Some((index, offset)) = self.fill_buffer(filter)? else {
+            return Ok(None);
+        };
+        if self.lenient {
+            return Ok(Some(TES3Object::load_lenient(&self.buffer, self.encoding)));
+        }
+        TES3Object::load_with_context(&self.buffer, index, offset, self.encoding).map(Some)
+    }
+
+    pub fn raw_records(&mut self) -> impl Iterator<Item = io::Result<RawRecord>> + '_ {
+        std::iter::from_fn(|| self.next_raw_record().transpose())
+    }
+
+    pub fn objects_filtered<'a, F>(&'a mut self, filter: F) -> impl Iterator<Item = io::Result<TES3Object>> + 'a
+    where
+        F: Fn([u8; 4]) -> bool + 'a,
+    {
+        std::iter::from_fn(move || self.next_object_filtered(&filter).transpose())
+    }
+
+    /// Fill the internal buffer with the next record accepted by `filter`.
+    ///
+    /// Returns the index and byte offset of the record, or `None` if the end of the stream was reached.
+    fn fill_buffer(&mut self, filter: impl Fn([u8; 4]) -> bool) -> io::Result<Option<(usize, u64)>> {
+        let mut header = [0u8; RawRecord::HEADER_SIZE];
+        loop {
+            if !read_exact_or_eof(&mut self.reader, &mut header)? {
+                return Ok(None);
+            }
+
+            let tag: [u8; 4] = header[0..4].try_into().unwrap_or_default();
+            let size = u32::from_le_bytes(header[4..8].try_into().unwrap_or_default());
+
+            let index = self.next_index;
+            let offset = self.next_offset;
+            self.next_index += 1;
+            self.next_offset += (RawRecord::HEADER_SIZE as u64) + u64::from(size);
+
+            if !filter(tag) {
+                self.reader.seek(SeekFrom::Current(size.into()))?;
+                continue;
+            }
+
+            self.buffer.clear();
+            self.buffer.extend_from_slice(&header);
+            self.buffer.resize(RawRecord::HEADER_SIZE + size as usize, 0);
+            self.reader.read_exact(&mut self.buffer[RawRecord::HEADER_SIZE..])?;
+
+            return Ok(Some((index, offset)));
+        }
+    }
+}
+
+impl<R: Read + Seek> Iterator for PluginReader<R> {
+    type Item = io::Result<TES3Object>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_object().transpose()
+    }
+}
+
+/// Like [`Read::read_exact`], but returns `Ok(false)` if the stream was already exhausted.
+fn read_exact_or_eof(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
@@ -0,0 +1,469 @@
+// rust std imports
+use std::fmt::{self, Display, Write};
+
+// internal imports
+use crate::prelude::*;
+
+/// A journal topic, see [`Plugin::quests`].
+///
+/// The [`Display`] implementation renders the quest as text, one line per stage:
+///
+/// ```text
+/// A1_1_FindSpymaster: Report to Caius Cosades
+///     1: I've been released from prison...
+///     10: Caius Cosades gave me a package... (restart)
+///     100: I've been promoted to Novice... (finished)
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Quest {
+    /// The editor id of the journal dialogue.
+    pub id: String,
+    /// The quest name, shown in the journal. Empty if the quest has no name info.
+    pub name: String,
+    /// The stages of the quest, ordered by index.
+    pub stages: Vec<QuestStage>,
+}
+
+/// A single journal entry of a [`Quest`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct QuestStage {
+    /// The journal index of the stage, as used by the `Journal` script function.
+    pub index: i32,
+    /// The journal text of the stage.
+    pub text: String,
+    /// Whether reaching this stage finishes the quest.
+    pub finished: bool,
+    /// Whether reaching this stage restarts a finished quest.
+    pub restart: bool,
+    /// The id of the info that defines this stage.
+    pub info_id: String,
+}
+
+impl Plugin {
+    /// All journal topics of the plugin, in order.
+    pub fn quests(&self) -> Vec<Quest> {
+        self.objects_of_type::<Dialogue>()
+            .filter(|dialogue| dialogue.dialogue_type == DialogueType2::Journal)
+            .filter_map(|dialogue| self.quest(&dialogue.id))
+            .collect()
+    }
+
+    /// The journal topic with the given id (ignoring case).
+    pub fn quest(&self, id: &str) -> Option<Quest> {
+        let topic = self.topic(id)?;
+        if topic.dialogue.dialogue_type != DialogueType2::Journal {
+            return None;
+        }
+        let mut quest = Quest {
+            id: topic.dialogue.id.clone(),
+            ..default()
+        };
+        for info in topic.infos {
+            if info.quest_state == Some(QuestState::Name) {
+                quest.name.clone_from(&info.text);
+                continue;
+            }
+            quest.stages.push(QuestStage {
+                index: info.data.disposition,
+                text: info.text.clone(),
+                finished: info.quest_state == Some(QuestState::Finished),
+                restart: info.quest_state == Some(QuestState::Restart),
+                info_id: info.id.clone(),
+            });
+        }
+        quest.stages.sort_by_key(|stage| stage.index);
+        Some(quest)
+    }
+
+    /// Add a stage to the journal topic with the given id.
+    ///
+    /// The new info is inserted after the name info and any stages with a lower index, and is
+    /// given a numeric id that is unique amongst all infos of the plugin. Returns the id of the
+    /// new info.
+    pub fn add_quest_stage(&mut self, id: &str, index: i32, text: &str) -> io::Result<String> {
+        let quest = self.expect_quest(id)?;
+        if quest.stages.iter().any(|stage| stage.index == index) {
+            let message = format!("Duplicate quest stage: {}::{index}", quest.id);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+
+        let info_id = self
+            .objects_of_type::<DialogueInfo>()
+            .filter_map(|info| info.id.parse::<u128>().ok())
+            .max()
+            .map_or(1, |max| max + 1)
+            .to_string();
+
+        let Some(mut topic) = self.topic_mut(id) else {
+            unreachable!();
+        };
+
+        let prev_id = topic
+            .infos()
+            .filter(|info| info.quest_state == Some(QuestState::Name) || info.data.disposition < index)
+            .last()
+            .map(|info| info.id.clone())
+            .unwrap_or_default();
+
+        let mut info = DialogueInfo {
+            id: info_id.clone(),
+            text: text.into(),
+            ..default()
+        };
+        info.data.dialogue_type = DialogueType::Journal;
+        info.data.disposition = index;
+        topic.insert_after(&prev_id, info)?;
+
+        Ok(info_id)
+    }
+
+    /// Renumber the stages of the journal topic with the given id to `step`, `2 * step`, etc.
+    ///
+    /// The order of the stages is kept, and journal filters of this plugin's infos are updated to
+    /// match the same stages as before. Filter values between two stages are moved between the
+    /// new indices, an error is returned if an `=` or `!=` filter has no room to keep its meaning.
+    /// Scripts are not updated.
+    pub fn renumber_quest_stages(&mut self, id: &str, step: i32) -> io::Result<()> {
+        let quest = self.expect_quest(id)?;
+        if let Some(index) = quest.duplicate_indices().first() {
+            let message = format!("Duplicate quest stage: {}::{index}", quest.id);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+        if step <= 0 {
+            let message = format!("Invalid quest stage step: {step}");
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+
+        let stages = StageMap::new(&quest, step)?;
+
+        // Remap all filters up front, so nothing is changed if one of them cannot be remapped.
+        let mut filter_values = vec![];
+        for info in self.objects_of_type::<DialogueInfo>() {
+            for (filter, value) in journal_filters(&info.filters, &quest.id) {
+                let new_value = stages.remap_filter(filter.comparison, value).ok_or_else(|| {
+                    let message = format!(
+                        "Cannot remap journal filter of info {}: {:?} {value}",
+                        info.id, filter.comparison
+                    );
+                    io::Error::new(io::ErrorKind::InvalidInput, message)
+                })?;
+                filter_values.push(new_value);
+            }
+        }
+
+        let Some(mut topic) = self.topic_mut(id) else {
+            unreachable!();
+        };
+        for info in topic.infos_mut() {
+            if info.quest_state == Some(QuestState::Name) {
+                continue;
+            }
+            if let Some(new_index) = stages.get(info.data.disposition) {
+                info.data.disposition = new_index;
+            }
+        }
+
+        let mut filter_values = filter_values.into_iter();
+        for info in self.objects_of_type_mut::<DialogueInfo>() {
+            for filter in &mut info.filters {
+                if let Some(value) = journal_filter_value_mut(filter, &quest.id) {
+                    *value = filter_values.next().unwrap_or(*value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn expect_quest(&self, id: &str) -> io::Result<Quest> {
+        self.quest(id).ok_or_else(|| {
+            let message = format!("Unknown journal topic: {id}");
+            io::Error::new(io::ErrorKind::InvalidInput, message)
+        })
+    }
+}
+
+/// The old and new indices of renumbered quest stages, in ascending order.
+struct StageMap {
+    points: Vec<(i32, i32)>,
+}
+
+impl StageMap {
+    fn new(quest: &Quest, step: i32) -> io::Result<Self> {
+        let mut points = vec![];
+        // The journal index of a quest that was not started yet is 0, which never changes.
+        if quest.stages.first().is_some_and(|stage| stage.index > 0) {
+            points.push((0, 0));
+        }
+        let mut new_index = 0i32;
+        for stage in &quest.stages {
+            new_index = new_index
+                .checked_add(step)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Quest stage index overflow"))?;
+            points.push((stage.index, new_index));
+        }
+        Ok(Self { points })
+    }
+
+    /// The new index of the stage with the old `index`.
+    fn get(&self, index: i32) -> Option<i32> {
+        let position = self.points.binary_search_by_key(&index, |&(old, _)| old).ok()?;
+        Some(self.points[position].1)
+    }
+
+    /// The new value of a journal filter, such that it matches the same stages as before.
+    ///
+    /// Returns `None` if there is no such value.
+    fn remap_filter(&self, comparison: FilterComparison, value: i32) -> Option<i32> {
+        let (&first, &last) = (self.points.first()?, self.points.last()?);
+        if value <= first.0 {
+            return value.checked_sub(first.0)?.checked_add(first.1);
+        }
+        if value >= last.0 {
+            return value.checked_sub(last.0)?.checked_add(last.1);
+        }
+        let position = self.points.partition_point(|&(old, _)| old < value);
+        let (below, above) = (self.points[position - 1], self.points[position]);
+        if above.0 == value {
+            return Some(above.1);
+        }
+        match comparison {
+            FilterComparison::Less | FilterComparison::GreaterEqual => Some(above.1),
+            FilterComparison::Greater | FilterComparison::LessEqual => Some(below.1),
+            FilterComparison::Equal | FilterComparison::NotEqual => {
+                let between = below.1 + 1;
+                (between < above.1).then_some(between)
+            }
+        }
+    }
+}
+
+/// The integer journal filters for the quest `id`, with their values.
+fn journal_filters<'a>(filters: &'a [Filter], id: &'a str) -> impl Iterator<Item = (&'a Filter, i32)> {
+    filters.iter().filter_map(move |filter| match filter.value {
+        FilterValue::Integer(value) if is_journal_filter(filter, id) => Some((filter, value)),
+        _ => None,
+    })
+}
+
+/// The value of `filter`, if it is an integer journal filter for the quest `id`.
+fn journal_filter_value_mut<'a>(filter: &'a mut Filter, id: &str) -> Option<&'a mut i32> {
+    if !is_journal_filter(filter, id) {
+        return None;
+    }
+    match &mut filter.value {
+        FilterValue::Integer(value) => Some(value),
+        FilterValue::Float(_) => None,
+    }
+}
+
+fn is_journal_filter(filter: &Filter, id: &str) -> bool {
+    filter.filter_type == FilterType::Journal && filter.id.eq_ignore_ascii_case(id)
+}
+
+impl Quest {
+    /// The stages that finish the quest.
+    pub fn finishing_stages(&self) -> impl Iterator<Item = &QuestStage> {
+        self.stages.iter().filter(|stage| stage.finished)
+    }
+
+    pub fn stage(&self, index: i32) -> Option<&QuestStage> {
+        self.stages.iter().find(|stage| stage.index == index)
+    }
+
+    /// The indices that are used by more than one stage, in ascending order.
+    pub fn duplicate_indices(&self) -> Vec<i32> {
+        let mut duplicates: Vec<i32> = self
+            .stages
+            .windows(2)
+            .filter(|pair| pair[0].index == pair[1].index)
+            .map(|pair| pair[0].index)
+            .collect();
+        duplicates.dedup();
+        duplicates
+    }
+
+    /// Render the quest as a graph in the Graphviz DOT language.
+    ///
+    /// Stages are linked in order of their index, finishing stages are drawn with a double border.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        let _ = writeln!(dot, "digraph {} {{", dot_string(&self.id));
+        let _ = writeln!(dot, "    label={};", dot_string(&self.name));
+        let _ = writeln!(dot, "    node [shape=box];");
+        for stage in &self.stages {
+            let shape = if stage.finished { " peripheries=2" } else { "" };
+            let label = dot_string(&format!("{}: {}", stage.index, stage.text));
+            let _ = writeln!(dot, "    {} [label={label}{shape}];", stage.index);
+        }
+        for pair in self.stages.windows(2) {
+            let _ = writeln!(dot, "    {} -> {};", pair[0].index, pair[1].index);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Quote `text` as a DOT string, with line breaks kept as centered lines.
+fn dot_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => {}
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl Display for Quest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.id, self.name)?;
+        for stage in &self.stages {
+            write!(f, "\n    {}: {}", stage.index, stage.text)?;
+            if stage.finished {
+                write!(f, " (finished)")?;
+            }
+            if stage.restart {
+                write!(f, " (restart)")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_plugin() -> Plugin {
+        let mut plugin = Plugin::new();
+        plugin.objects.push(
+            Dialogue {
+                id: "test_quest".into(),
+                dialogue_type: DialogueType2::Journal,
+                ..default()
+            }
+            .into(),
+        );
+        let mut topic = plugin.topic_mut("test_quest").unwrap();
+        topic.push(DialogueInfo {
+            id: "1".into(),
+            text: "Test Quest".into(),
+            quest_state: Some(QuestState::Name),
+            ..default()
+        });
+        plugin.add_quest_stage("test_quest", 20, "Finished.").unwrap();
+        plugin.add_quest_stage("test_quest", 10, "Started.").unwrap();
+        plugin
+    }
+
+    #[test]
+    fn quests() {
+        let mut plugin = test_plugin();
+        plugin.objects_of_type_mut::<DialogueInfo>().last().unwrap().quest_state = Some(QuestState::Finished);
+
+        let quests = plugin.quests();
+        assert_eq!(quests.len(), 1);
+        assert_eq!(
+            quests[0].to_string(),
+            "test_quest: Test Quest\n    10: Started.\n    20: Finished. (finished)"
+        );
+        assert_eq!(quests[0].finishing_stages().count(), 1);
+        assert!(quests[0].to_dot().contains("10 -> 20;"));
+
+        // Infos are ordered by index, after the name.
+        let topic = plugin.topic("test_quest").unwrap();
+        let ids: Vec<_> = topic.infos.iter().map(|info| info.id.as_str()).collect();
+        assert_eq!(ids, ["1", "3", "2"]);
+
+        let error = plugin.add_quest_stage("test_quest", 10, "Duplicate.");
+        assert!(error.is_err());
+
+        // Info ids are unique across all topics.
+        plugin.objects.push(
+            Dialogue {
+                id: "test_topic".into(),
+                ..default()
+            }
+            .into(),
+        );
+        plugin.topic_mut("test_topic").unwrap().push(DialogueInfo {
+            id: "7".into(),
+            ..default()
+        });
+        assert_eq!(plugin.add_quest_stage("test_quest", 30, "Later.").unwrap(), "8");
+    }
+
+    #[test]
+    fn to_dot() {
+        let quest = Quest {
+            id: "test_quest".into(),
+            name: "Say \"Hi\"".into(),
+            stages: vec![QuestStage {
+                index: 10,
+                text: "C:\\Morrowind\r\nDone.".into(),
+                ..default()
+            }],
+        };
+        let dot = quest.to_dot();
+        assert!(dot.contains(r#"label="Say \"Hi\"";"#), "{dot}");
+        assert!(dot.contains(r#"10 [label="10: C:\\Morrowind\nDone."];"#), "{dot}");
+    }
+
+    #[test]
+    fn renumber_quest_stages() {
+        let mut plugin = test_plugin();
+        plugin.objects.push(
+            Dialogue {
+                id: "test_topic".into(),
+                ..default()
+            }
+            .into(),
+        );
+        let filter = |comparison, value| Filter {
+            filter_type: FilterType::Journal,
+            comparison,
+            id: "TEST_QUEST".into(),
+            value: FilterValue::Integer(value),
+            ..default()
+        };
+        plugin.objects.push(
+            DialogueInfo {
+                id: "4".into(),
+                filters: vec![
+                    filter(FilterComparison::Equal, 20),
+                    filter(FilterComparison::Less, 12),
+                    filter(FilterComparison::Greater, 12),
+                    filter(FilterComparison::GreaterEqual, 5),
+                    filter(FilterComparison::Equal, 0),
+                    filter(FilterComparison::Greater, 25),
+                ],
+                ..default()
+            }
+            .into(),
+        );
+
+        plugin.renumber_quest_stages("test_quest", 5).unwrap();
+
+        let quest = plugin.quest("test_quest").unwrap();
+        let indices: Vec<_> = quest.stages.iter().map(|stage| (stage.index, stage.text.as_str())).collect();
+        assert_eq!(indices, [(5, "Started."), (10, "Finished.")]);
+
+        let info = plugin.objects_of_type::<DialogueInfo>().last().unwrap();
+        let values: Vec<_> = info.filters.iter().map(|filter| filter.value).collect();
+        let expected = [10, 10, 5, 5, 0, 15].map(FilterValue::Integer);
+        assert_eq!(values, expected);
+
+        // There is no index between 1 and 2 for `= 7` to keep matching no stage.
+        plugin.objects_of_type_mut::<DialogueInfo>().last().unwrap().filters = vec![filter(FilterComparison::Equal, 7)];
+        assert!(plugin.renumber_quest_stages("test_quest", 1).is_err());
+        let quest = plugin.quest("test_quest").unwrap();
+        assert_eq!(quest.stages[0].index, 5);
+    }
+}
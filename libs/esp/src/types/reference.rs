@@ -1,3 +1,6 @@
+// external imports
+use glam::{Affine3A, EulerRot, Mat3, Vec3};
+
 // internal imports
 use crate::prelude::*;
 
@@ -100,7 +103,7 @@ impl Load for Reference {
                     break;
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: REFR::{}", tag.to_str_lossy()))?;
+                    stream.unexpected_tag("REFR", tag)?;
                 }
             }
         }
@@ -242,6 +245,53 @@ impl Reference {
         !self.temporary
     }
 
+    /// The transform from the object's local space to world space.
+    ///
+    /// Rotations are applied around the Z, Y, then X world axes, with the angles negated. Scales
+    /// are clamped to the range the game supports, like when saving.
+    pub fn transform(&self) -> Affine3A {
+        let [x, y, z] = self.rotation;
+        let rotation = Mat3::from_euler(EulerRot::XYZ, -x, -y, -z);
+        let scale = self.scale.unwrap_or(1.0).clamp(0.5, 2.0);
+        Affine3A::from_mat3_translation(rotation * scale, self.translation.into())
+    }
+
+    /// Set the translation, rotation, and scale from a transform, see [`Reference::transform`].
+    ///
+    /// The transform should not contain shearing or non-uniform scales, as these cannot be stored.
+    /// Non-uniform scales are averaged, and scales are clamped to the range the game supports.
+    pub fn set_transform(&mut self, transform: Affine3A) {
+        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+        let [x, y, z] = rotation.to_euler(EulerRot::XYZ).into();
+        self.translation = translation.into();
+        self.rotation = [-x, -y, -z];
+
+        let scale = (scale.element_sum() / 3.0).clamp(0.5, 2.0);
+        let scale_is_default = (scale - 1.0).abs() < 1e-6;
+        self.scale = if scale_is_default && self.mast_index == 0 {
+            None
+        } else {
+            Some(scale)
+        };
+    }
+
+    /// Transform an object space bounding box into a world space bounding box of this reference.
+    ///
+    /// The bounds of a mesh are usually found with `nif::NiStream::bounding_box`. The result
+    /// encloses the transformed corners of the box.
+    pub fn world_bounds(&self, (min, max): (Vec3, Vec3)) -> (Vec3, Vec3) {
+        let transform = self.transform();
+        let corners = (0..8).map(|i| {
+            let x = if i & 1 == 0 { min.x } else { max.x };
+            let y = if i & 2 == 0 { min.y } else { max.y };
+            let z = if i & 4 == 0 { min.z } else { max.z };
+            transform.transform_point3(Vec3::new(x, y, z))
+        });
+        corners.fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(min, max), corner| {
+            (min.min(corner), max.max(corner))
+        })
+    }
+
     pub(crate) fn make_transforms_finite(&mut self) {
         for value in &mut self.translation {
             if !value.is_finite() {
@@ -258,3 +308,44 @@ impl Reference {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Quat;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn transform() {
+        let mut reference = Reference {
+            translation: [100.0, 200.0, 300.0],
+            rotation: [0.0, 0.0, FRAC_PI_2],
+            scale: Some(4.0),
+            ..default()
+        };
+
+        // Positive angles rotate clockwise, and the scale is clamped.
+        let transform = reference.transform();
+        let point = transform.transform_point3(Vec3::X);
+        assert!(point.abs_diff_eq(Vec3::new(100.0, 198.0, 300.0), 1e-4), "{point}");
+
+        let bounds = reference.world_bounds((Vec3::splat(-1.0), Vec3::new(1.0, 2.0, 3.0)));
+        assert!(bounds.0.abs_diff_eq(Vec3::new(98.0, 198.0, 298.0), 1e-4), "{bounds:?}");
+        assert!(bounds.1.abs_diff_eq(Vec3::new(104.0, 202.0, 306.0), 1e-4), "{bounds:?}");
+
+        // Rotations are applied around Z, then Y, then X.
+        reference.rotation = [0.3, -0.2, 1.1];
+        let expected = Quat::from_rotation_x(-0.3) * Quat::from_rotation_y(0.2) * Quat::from_rotation_z(-1.1);
+        let (_, rotation, _) = reference.transform().to_scale_rotation_translation();
+        assert!(rotation.abs_diff_eq(expected, 1e-5), "{rotation} {expected}");
+
+        let mut other = Reference::default();
+        other.set_transform(reference.transform());
+        assert_eq!(other.scale, Some(2.0));
+        assert!(Vec3::from(other.rotation).abs_diff_eq(Vec3::new(0.3, -0.2, 1.1), 1e-5));
+        assert!(Vec3::from(other.translation).abs_diff_eq(Vec3::new(100.0, 200.0, 300.0), 1e-4));
+
+        other.set_transform(Affine3A::IDENTITY);
+        assert_eq!(other.scale, None);
+    }
+}
@@ -11,6 +11,7 @@ pub struct Region {
     pub sleep_creature: String,
     pub map_color: [u8; 4],
     pub sounds: Vec<(FixedString<32>, u8)>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -62,7 +63,7 @@ impl Load for Region {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
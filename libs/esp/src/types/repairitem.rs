@@ -11,6 +11,7 @@ pub struct RepairItem {
     pub mesh: String,
     pub icon: String,
     pub data: RepairItemData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -55,7 +56,7 @@ impl Load for RepairItem {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -0,0 +1,210 @@
+// rust std imports
+use std::path::Path;
+
+// internal imports
+use crate::prelude::*;
+
+/// A saved game (`.ess`) file.
+///
+/// Saved games use the same container format as plugins, but contain additional record types
+/// (`GAME`, `PLAY`, `JOUR`, `QUES`, `SPLM`, `FMAP`, `NPCC`, `CNTC`, `CREC`, ...) and store the
+/// reference change data in their `CELL` records. Saves are always loaded in lenient mode, so
+/// anything not understood is kept as [`TES3Object::Unknown`] and written back unchanged.
+///
+/// Records of save specific types, including the saved cells, are kept as [`TES3Object::Unknown`]
+/// and decoded on demand with [`SaveGame::records_of_type`], see [`SaveRecord`] for the supported
+/// types.
+///
+/// ```no_run
+/// use esp::{Journal, SaveGame};
+///
+/// let save = SaveGame::from_path("quiksave.ess")?;
+/// if let Some(data) = save.game_data()? {
+///     println!("{} in {}", data.player_name.as_str(), data.cell_name.as_str());
+/// }
+/// for journal in save.records_of_type::<Journal>() {
+///     println!("{}", journal?.text);
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, Deref, DerefMut)]
+pub struct SaveGame {
+    pub plugin: Plugin,
+}
+
+/// The `GMDT` subrecord of a saved game header.
+#[esp_meta]
+#[derive(LoadSave, Clone, Debug, Default, PartialEq)]
+pub struct SaveGameData {
+    pub current_health: f32,
+    pub max_health: f32,
+    pub hour: f32,
+    pub day: i32,
+    pub month: i32,
+    pub year: i32,
+    pub cell_name: FixedString<64>,
+    pub days_passed: f32,
+    pub player_name: FixedString<32>,
+}
+
+impl SaveGame {
+    pub const SCREENSHOT_WIDTH: usize = 128;
+    pub const SCREENSHOT_HEIGHT: usize = 128;
+
+    pub fn new() -> Self {
+        default()
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut save = Self::new();
+        save.load_path(path)?;
+        Ok(save)
+    }
+
+    pub fn load_path(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.load_bytes(&std::fs::read(path)?)
+    }
+
+    pub fn load_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let encoding = self.encoding;
+        self.plugin.load_objects(
+            bytes,
+            |_| true,
+            |bytes, _, _| {
+                // The reference change data of saved cells is not understood by `Cell`, these are
+                // decoded with `CellChanges` instead.
+                if bytes.starts_with(Cell::TAG) {
+                    return Ok(TES3Object::Unknown(UnknownRecord::from_bytes(bytes, encoding)));
+                }
+                Ok(TES3Object::load_lenient(bytes, encoding))
+            },
+        )
+    }
+
+    /// Decode the `GMDT` subrecord of the save header.
+    pub fn game_data(&self) -> io::Result<Option<SaveGameData>> {
+        self.header_subrecord(*b"GMDT")
+            .map(|data| self.reader(data).load())
+            .transpose()
+    }
+
+    pub fn set_game_data(&mut self, game_data: &SaveGameData) -> io::Result<()> {
+        let mut stream = self.writer();
+        stream.save(game_data)?;
+        self.set_header_subrecord(*b"GMDT", stream.cursor.into_inner())
+    }
+
+    /// The save screenshot as BGRA pixels, see [`SaveGame::SCREENSHOT_WIDTH`].
+    pub fn screenshot(&self) -> Option<&[u8]> {
+        self.header_subrecord(*b"SCRS")
+    }
+
+    pub fn set_screenshot(&mut self, pixels: Vec<u8>) -> io::Result<()> {
+        let expected = Self::SCREENSHOT_WIDTH * Self::SCREENSHOT_HEIGHT * 4;
+        if pixels.len() != expected {
+            Writer::error(format!("Invalid screenshot size ({} != {expected})", pixels.len()))?;
+        }
+        self.set_header_subrecord(*b"SCRS", pixels)
+    }
+
+    /// The player's `NPC_` record.
+    pub fn player(&self) -> Option<&Npc> {
+        self.objects_of_type::<Npc>()
+            .find(|npc| npc.id.eq_ignore_ascii_case("player"))
+    }
+
+    /// The player's level.
+    ///
+    /// The save header does not store the level, the game shows the level from the player's
+    /// `NPC_` record which is rewritten with the current stats on every save.
+    pub fn player_level(&self) -> Option<i16> {
+        self.player().map(|player| player.data.level)
+    }
+
+    /// Decode all records of the save specific type `T`.
+    pub fn records_of_type<T: SaveRecord>(&self) -> impl Iterator<Item = io::Result<T>> + '_ {
+        self.objects.iter().filter_map(|object| match object {
+            TES3Object::Unknown(record) if &record.tag == T::TAG => Some(self.load_record(&record.bytes)),
+            _ => None,
+        })
+    }
+
+    /// Replace all records of the save specific type `T`.
+    ///
+    /// The new records are inserted where the first of the previous records was, or at the end.
+    pub fn set_records_of_type<T: SaveRecord>(&mut self, records: impl IntoIterator<Item = T>) -> io::Result<()> {
+        let is_type = |object: &TES3Object| matches!(object, TES3Object::Unknown(record) if &record.tag == T::TAG);
+
+        let records = records
+            .into_iter()
+            .map(|record| {
+                let mut stream = self.writer();
+                stream.save(&record)?;
+                Ok(TES3Object::Unknown(UnknownRecord {
+                    tag: *T::TAG,
+                    flags: default(),
+                    bytes: stream.cursor.into_inner(),
+                    encoding: self.encoding,
+                }))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let position = self.objects.iter().position(is_type).unwrap_or(self.objects.len());
+        self.objects.retain(|object| !is_type(object));
+        self.objects.splice(position..position, records);
+
+        Ok(())
+    }
+
+    /// Decode a save record, keeping any unknown subrecords.
+    fn load_record<T: SaveRecord>(&self, bytes: &[u8]) -> io::Result<T> {
+        let mut stream = self.reader(bytes);
+        stream.skipped_subrecords = Some(vec![]);
+        let mut record: T = stream.load()?;
+        let skipped = stream.skipped_subrecords.unwrap_or_default();
+        *record.unknown_subrecords_mut() = UnknownSubrecord::from_skipped(bytes, 0, &skipped);
+        Ok(record)
+    }
+
+    fn reader<'a>(&self, bytes: &'a [u8]) -> Reader<'a> {
+        let mut stream = Reader::new(bytes);
+        stream.encoding = self.encoding;
+        stream
+    }
+
+    fn writer(&self) -> Writer {
+        let mut stream = Writer::new(vec![]);
+        stream.encoding = self.encoding;
+        stream
+    }
+
+    fn header_subrecord(&self, tag: [u8; 4]) -> Option<&[u8]> {
+        self.header()?
+            .unknown_subrecords
+            .iter()
+            .find(|subrecord| subrecord.tag == tag)
+            .map(|subrecord| subrecord.data.as_slice())
+    }
+
+    fn set_header_subrecord(&mut self, tag: [u8; 4], data: Vec<u8>) -> io::Result<()> {
+        let Some(header) = self.header_mut() else {
+            return Writer::error("Missing save header");
+        };
+
+        if let Some(subrecord) = header.unknown_subrecords.iter_mut().find(|subrecord| subrecord.tag == tag) {
+            subrecord.data = data;
+        } else {
+            // New subrecords go after the HEDR and MAST/DATA subrecords.
+            let index = 1 + 2 * header.masters.len() + header.unknown_subrecords.len();
+            header.unknown_subrecords.push(UnknownSubrecord {
+                index: index
+                    .try_into()
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "too many masters"))?,
+                tag,
+                data,
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1052 @@
+// internal imports
+use crate::prelude::*;
+
+/// A record type that only exists in saved games, see [`SaveGame::records_of_type`].
+///
+/// Implementors load and save the subrecords of the record, excluding the record header. Subrecords
+/// that are not understood are kept in `unknown_subrecords` and written back in their original
+/// position.
+pub trait SaveRecord: Load + Save {
+    const TAG: &'static [u8; 4];
+
+    fn unknown_subrecords_mut(&mut self) -> &mut Vec<UnknownSubrecord>;
+}
+
+macro_rules! impl_save_record {
+    ($($ty:ty => $tag:literal),* $(,)?) => {
+        $(
+            impl SaveRecord for $ty {
+                const TAG: &'static [u8; 4] = $tag;
+
+                fn unknown_subrecords_mut(&mut self) -> &mut Vec<UnknownSubrecord> {
+                    &mut self.unknown_subrecords
+                }
+            }
+        )*
+    };
+}
+
+impl_save_record! {
+    GameState => b"GAME",
+    Journal => b"JOUR",
+    QuestProgress => b"QUES",
+    ActiveSpells => b"SPLM",
+    GlobalMap => b"FMAP",
+    NpcChanges => b"NPCC",
+    ContainerChanges => b"CNTC",
+    CreatureChanges => b"CREC",
+    CellChanges => b"CELL",
+}
+
+/// The weather and moon phases (`GAME`).
+#[esp_meta]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GameState {
+    pub cell_name: FixedString<64>,
+    pub fog_color: [u8; 4],
+    pub fog_density: f32,
+    pub current_weather: i32,
+    pub next_weather: i32,
+    /// The progress of the transition to the next weather, from 0 to 100.
+    pub weather_transition: i32,
+    /// The game hour at which the weather changes next.
+    pub next_transition_hour: f32,
+    pub masser_phase: i32,
+    /// Older saves do not store the phase of Secunda.
+    pub secunda_phase: Option<i32>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
+}
+
+/// The player's journal (`JOUR`).
+#[esp_meta]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Journal {
+    pub text: String,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
+}
+
+/// The journal entries that were added for a single quest (`QUES`).
+#[esp_meta]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct QuestProgress {
+    pub id: String,
+    pub info_ids: Vec<String>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
+}
+
+/// The spells, enchantments, and potions that are currently in effect (`SPLM`).
+#[esp_meta]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ActiveSpells {
+    pub spells: Vec<ActiveSpell>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
+}
+
+/// A single active spell, starting with its `NAME` subrecord.
+///
+/// The `unknown_subrecords` hold the state of the individual effects (`NPDT`, `INAM`, `CNAM`,
+/// `VNAM`, `NAM0`, `XNAM`), indexed by their position within the spell.
+#[esp_meta]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ActiveSpell {
+    pub index: i32,
+    pub data: ActiveSpellData,
+    pub target: Option<String>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
+}
+
+#[esp_meta]
+#[derive(LoadSave, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ActiveSpellData {
+    /// 1 for spells, 2 for enchantments, and 3 for potions.
+    pub spell_type: i32,
+    pub id: FixedString<32>,
+    pub unknown1: [u32; 4],
+    pub caster_id: FixedString<32>,
+    /// The enchanted item or potion, empty for spells.
+    pub source_id: FixedString<32>,
+    pub unknown2: [u32; 11],
+}
+
+/// The world map (`FMAP`).
+#[esp_meta]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GlobalMap {
+    /// The width and height of the map, in pixels.
+    pub size: u32,
+    pub unknown: u32,
+    /// The map as RGB pixels.
+    pub pixels: Vec<u8>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
+}
+
+/// The changes to an NPC (`NPCC`).
+#[esp_meta]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NpcChanges {
+    pub id: String,
+    pub data: NpcChangesData,
+    pub inventory: Inventory,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
+}
+
+#[esp_meta]
+#[derive(LoadSave, Clone, Debug, Default, Eq, PartialEq)]
+pub struct NpcChangesData {
+    pub disposition: u8,
+    pub unknown1: u8,
+    pub reputation: u8,
+    pub unknown2: u8,
+    pub index: i32,
+}
+
+/// The changes to a container (`CNTC`).
+#[esp_meta]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ContainerChanges {
+    pub id: String,
+    pub index: u32,
+    pub inventory: Inventory,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
+}
+
+/// The changes to a creature (`CREC`).
+#[esp_meta]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CreatureChanges {
+    pub id: String,
+    pub index: u32,
+    pub scale: Option<f32>,
+    pub inventory: Inventory,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
+}
+
+/// The inventory of an NPC, creature, or container change record.
+#[esp_meta]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Inventory {
+    pub items: Vec<InventoryItem>,
+    /// The equipped items (`WIDX`), as an index into `items` and the equipment slot relative to
+    /// the slots that item can use.
+    pub equipped: Vec<(i32, i32)>,
+}
+
+/// A stack of items (`NPCO`).
+///
+/// The `unknown_subrecords` hold the state of the individual items that follow the `NPCO`
+/// subrecord (`XIDX`, `SCRI`, `XSOL`, `XCHG`, `XHLT`, ...), indexed by their position within the
+/// stack.
+#[esp_meta]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InventoryItem {
+    pub count: i32,
+    pub id: FixedString<32>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
+}
+
+/// The changes to a cell and its references (`CELL`).
+///
+/// The `unknown_subrecords` hold the cell state that is not decoded, such as the fog of war
+/// (`NAM8`) and map notes (`MPCD`, `MPNT`).
+#[esp_meta]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CellChanges {
+    pub name: Option<String>,
+    pub data: Option<CellData>,
+    pub references: Vec<ReferenceChanges>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
+}
+
+/// The changes to a single reference, starting with its `FRMR` subrecord.
+///
+/// The `unknown_subrecords` hold the change data of the reference (`ACDT`, `CHRD`, `ND3D`,
+/// `ANIS`, `ZNAM`, `DELE`, ...), indexed by their position within the reference.
+#[esp_meta]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReferenceChanges {
+    pub mast_index: u32,
+    pub refr_index: u32,
+    pub id: Option<String>,
+    /// The translation and rotation (`DATA`), missing for leveled creature spawners.
+    pub transform: Option<([f32; 3], [f32; 3])>,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
+}
+
+impl Load for GameState {
+    fn load(stream: &mut Reader<'_>) -> io::Result<Self> {
+        let mut this: Self = default();
+
+        while let Ok(tag) = stream.load() {
+            match &tag {
+                b"GMDT" => {
+                    let size: u32 = stream.load()?;
+                    if size != 92 && size != 96 {
+                        return Reader::error(format!("Invalid GAME::GMDT size ({size})"));
+                    }
+                    this.cell_name = stream.load()?;
+                    this.fog_color = stream.load()?;
+                    this.fog_density = stream.load()?;
+                    this.current_weather = stream.load()?;
+                    this.next_weather = stream.load()?;
+                    this.weather_transition = stream.load()?;
+                    this.next_transition_hour = stream.load()?;
+                    this.masser_phase = stream.load()?;
+                    if size == 96 {
+                        this.secunda_phase = Some(stream.load()?);
+                    }
+                }
+                _ => {
+                    stream.unexpected_tag("GAME", tag)?;
+                }
+            }
+        }
+
+        Ok(this)
+    }
+}
+
+impl Save for GameState {
+    fn save(&self, stream: &mut Writer) -> io::Result<()> {
+        let start = stream.cursor.position();
+        // GMDT
+        stream.save(b"GMDT")?;
+        let size: u32 = if self.secunda_phase.is_some() { 96 } else { 92 };
+        stream.save(&size)?;
+        stream.save(&self.cell_name)?;
+        stream.save(&self.fog_color)?;
+        stream.save(&self.fog_density)?;
+        stream.save(&self.current_weather)?;
+        stream.save(&self.next_weather)?;
+        stream.save(&self.weather_transition)?;
+        stream.save(&self.next_transition_hour)?;
+        stream.save(&self.masser_phase)?;
+        if let Some(value) = &self.secunda_phase {
+            stream.save(value)?;
+        }
+        UnknownSubrecord::insert_all(stream, start, &self.unknown_subrecords)
+    }
+}
+
+impl Load for Journal {
+    fn load(stream: &mut Reader<'_>) -> io::Result<Self> {
+        let mut this: Self = default();
+
+        while let Ok(tag) = stream.load() {
+            match &tag {
+                b"NAME" => {
+                    let len = stream.load_as::<u32, usize>()?;
+                    this.text = stream.load_string(len)?;
+                }
+                _ => {
+                    stream.unexpected_tag("JOUR", tag)?;
+                }
+            }
+        }
+
+        Ok(this)
+    }
+}
+
+impl Save for Journal {
+    fn save(&self, stream: &mut Writer) -> io::Result<()> {
+        let start = stream.cursor.position();
+        // NAME
+        stream.save(b"NAME")?;
+        stream.save_string_without_null_terminator(&self.text)?;
+        UnknownSubrecord::insert_all(stream, start, &self.unknown_subrecords)
+    }
+}
+
+impl Load for QuestProgress {
+    fn load(stream: &mut Reader<'_>) -> io::Result<Self> {
+        let mut this: Self = default();
+
+        while let Ok(tag) = stream.load() {
+            match &tag {
+                b"NAME" => {
+                    this.id = stream.load()?;
+                }
+                b"DATA" => {
+                    this.info_ids.push(stream.load()?);
+                }
+                _ => {
+                    stream.unexpected_tag("QUES", tag)?;
+                }
+            }
+        }
+
+        Ok(this)
+    }
+}
+
+impl Save for QuestProgress {
+    fn save(&self, stream: &mut Writer) -> io::Result<()> {
+        let start = stream.cursor.position();
+        // NAME
+        stream.save(b"NAME")?;
+        stream.save(&self.id)?;
+        // DATA
+        for info_id in &self.info_ids {
+            stream.save(b"DATA")?;
+            stream.save(info_id)?;
+        }
+        UnknownSubrecord::insert_all(stream, start, &self.unknown_subrecords)
+    }
+}
+
+impl Load for ActiveSpells {
+    fn load(stream: &mut Reader<'_>) -> io::Result<Self> {
+        let mut this: Self = default();
+
+        while let Ok(tag) = stream.load() {
+            match (&tag, this.spells.last_mut()) {
+                (b"NAME", _) => {
+                    stream.expect(4u32)?;
+                    let index = stream.load()?;
+                    // "NAME" is always followed by "SPDT"
+                    stream.expect(*b"SPDT")?;
+                    stream.expect(160u32)?;
+                    this.spells.push(ActiveSpell {
+                        index,
+                        data: stream.load()?,
+                        ..default()
+                    });
+                }
+                (_, Some(spell)) => {
+                    spell.load_subrecord(stream, tag)?;
+                }
+                (_, None) => {
+                    stream.unexpected_tag("SPLM", tag)?;
+                }
+            }
+        }
+
+        Ok(this)
+    }
+}
+
+impl Save for ActiveSpells {
+    fn save(&self, stream: &mut Writer) -> io::Result<()> {
+        let start = stream.cursor.position();
+        for spell in &self.spells {
+            stream.save(spell)?;
+        }
+        UnknownSubrecord::insert_all(stream, start, &self.unknown_subrecords)
+    }
+}
+
+impl ActiveSpell {
+    /// Load a subrecord following the `NAME` and `SPDT` subrecords of the spell.
+    fn load_subrecord(&mut self, stream: &mut Reader<'_>, tag: [u8; 4]) -> io::Result<()> {
+        let index = 2 + usize::from(self.target.is_some()) + self.unknown_subrecords.len();
+        let len: u32 = stream.load()?;
+        match &tag {
+            b"TNAM" if index == 2 => {
+                self.target = Some(stream.load_string(len as usize)?);
+            }
+            _ => {
+                self.unknown_subrecords.push(load_unknown(stream, tag, len, index)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Save for ActiveSpell {
+    fn save(&self, stream: &mut Writer) -> io::Result<()> {
+        let start = stream.cursor.position();
+        // NAME
+        stream.save(b"NAME")?;
+        stream.save(&4u32)?;
+        stream.save(&self.index)?;
+        // SPDT
+        stream.save(b"SPDT")?;
+        stream.save(&160u32)?;
+        stream.save(&self.data)?;
+        // TNAM
+        if let Some(value) = &self.target {
+            stream.save(b"TNAM")?;
+            stream.save(value)?;
+        }
+        UnknownSubrecord::insert_all(stream, start, &self.unknown_subrecords)
+    }
+}
+
+impl Load for GlobalMap {
+    fn load(stream: &mut Reader<'_>) -> io::Result<Self> {
+        let mut this: Self = default();
+
+        while let Ok(tag) = stream.load() {
+            match &tag {
+                b"MAPH" => {
+                    stream.expect(8u32)?;
+                    this.size = stream.load()?;
+                    this.unknown = stream.load()?;
+                }
+                b"MAPD" => {
+                    let len = stream.load_as::<u32, usize>()?;
+                    this.pixels = stream.load_bytes(len)?;
+                }
+                _ => {
+                    stream.unexpected_tag("FMAP", tag)?;
+                }
+            }
+        }
+
+        Ok(this)
+    }
+}
+
+impl Save for GlobalMap {
+    fn save(&self, stream: &mut Writer) -> io::Result<()> {
+        let start = stream.cursor.position();
+        // MAPH
+        stream.save(b"MAPH")?;
+        stream.save(&8u32)?;
+        stream.save(&self.size)?;
+        stream.save(&self.unknown)?;
+        // MAPD
+        stream.save(b"MAPD")?;
+        stream.save_as::<u32>(self.pixels.len())?;
+        stream.save_bytes(&self.pixels)?;
+        UnknownSubrecord::insert_all(stream, start, &self.unknown_subrecords)
+    }
+}
+
+impl Load for NpcChanges {
+    fn load(stream: &mut Reader<'_>) -> io::Result<Self> {
+        let mut this: Self = default();
+
+        while let Ok(tag) = stream.load() {
+            match &tag {
+                b"NAME" => {
+                    this.id = stream.load()?;
+                }
+                b"NPDT" => {
+                    stream.expect(8u32)?;
+                    this.data = stream.load()?;
+                }
+                _ => {
+                    if !this.inventory.load_subrecord(stream, tag)? {
+                        stream.unexpected_tag("NPCC", tag)?;
+                    }
+                }
+            }
+        }
+
+        Ok(this)
+    }
+}
+
+impl Save for NpcChanges {
+    fn save(&self, stream: &mut Writer) -> io::Result<()> {
+        let start = stream.cursor.position();
+        // NAME
+        stream.save(b"NAME")?;
+        stream.save(&self.id)?;
+        // NPDT
+        stream.save(b"NPDT")?;
+        stream.save(&8u32)?;
+        stream.save(&self.data)?;
+        // NPCO, WIDX
+        stream.save(&self.inventory)?;
+        UnknownSubrecord::insert_all(stream, start, &self.unknown_subrecords)
+    }
+}
+
+impl Load for ContainerChanges {
+    fn load(stream: &mut Reader<'_>) -> io::Result<Self> {
+        let mut this: Self = default();
+
+        while let Ok(tag) = stream.load() {
+            match &tag {
+                b"NAME" => {
+                    this.id = stream.load()?;
+                }
+                b"INDX" => {
+                    stream.expect(4u32)?;
+                    this.index = stream.load()?;
+                }
+                _ => {
+                    if !this.inventory.load_subrecord(stream, tag)? {
+                        stream.unexpected_tag("CNTC", tag)?;
+                    }
+                }
+            }
+        }
+
+        Ok(this)
+    }
+}
+
+impl Save for ContainerChanges {
+    fn save(&self, stream: &mut Writer) -> io::Result<()> {
+        let start = stream.cursor.position();
+        // NAME
+        stream.save(b"NAME")?;
+        stream.save(&self.id)?;
+        // INDX
+        stream.save(b"INDX")?;
+        stream.save(&4u32)?;
+        stream.save(&self.index)?;
+        // NPCO, WIDX
+        stream.save(&self.inventory)?;
+        UnknownSubrecord::insert_all(stream, start, &self.unknown_subrecords)
+    }
+}
+
+impl Load for CreatureChanges {
+    fn load(stream: &mut Reader<'_>) -> io::Result<Self> {
+        let mut this: Self = default();
+
+        while let Ok(tag) = stream.load() {
+            match &tag {
+                b"NAME" => {
+                    this.id = stream.load()?;
+                }
+                b"INDX" => {
+                    stream.expect(4u32)?;
+                    this.index = stream.load()?;
+                }
+                b"XSCL" if this.inventory.items.is_empty() => {
+                    stream.expect(4u32)?;
+                    this.scale = Some(stream.load()?);
+                }
+                _ => {
+                    if !this.inventory.load_subrecord(stream, tag)? {
+                        stream.unexpected_tag("CREC", tag)?;
+                    }
+                }
+            }
+        }
+
+        Ok(this)
+    }
+}
+
+impl Save for CreatureChanges {
+    fn save(&self, stream: &mut Writer) -> io::Result<()> {
+        let start = stream.cursor.position();
+        // NAME
+        stream.save(b"NAME")?;
+        stream.save(&self.id)?;
+        // INDX
+        stream.save(b"INDX")?;
+        stream.save(&4u32)?;
+        stream.save(&self.index)?;
+        // XSCL
+        if let Some(value) = &self.scale {
+            stream.save(b"XSCL")?;
+            stream.save(&4u32)?;
+            stream.save(value)?;
+        }
+        // NPCO, WIDX
+        stream.save(&self.inventory)?;
+        UnknownSubrecord::insert_all(stream, start, &self.unknown_subrecords)
+    }
+}
+
+impl Inventory {
+    /// Load a subrecord of the inventory, returning `false` if `tag` is not part of it.
+    ///
+    /// Subrecords following an `NPCO` subrecord belong to that stack, up to the next `NPCO` or the
+    /// first `WIDX` subrecord.
+    fn load_subrecord(&mut self, stream: &mut Reader<'_>, tag: [u8; 4]) -> io::Result<bool> {
+        match &tag {
+            b"NPCO" => {
+                stream.expect(36u32)?;
+                let (count, id) = stream.load()?;
+                self.items.push(InventoryItem { count, id, ..default() });
+            }
+            b"WIDX" => {
+                stream.expect(8u32)?;
+                self.equipped.push(stream.load()?);
+            }
+            _ => {
+                let Some(item) = self.items.last_mut().filter(|_| self.equipped.is_empty()) else {
+                    return Ok(false);
+                };
+                let index = 1 + item.unknown_subrecords.len();
+                let len: u32 = stream.load()?;
+                item.unknown_subrecords.push(load_unknown(stream, tag, len, index)?);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl Save for Inventory {
+    fn save(&self, stream: &mut Writer) -> io::Result<()> {
+        // NPCO
+        for item in &self.items {
+            let start = stream.cursor.position();
+            stream.save(b"NPCO")?;
+            stream.save(&36u32)?;
+            stream.save(&item.count)?;
+            stream.save(&item.id)?;
+            UnknownSubrecord::insert_all(stream, start, &item.unknown_subrecords)?;
+        }
+        // WIDX
+        for value in &self.equipped {
+            stream.save(b"WIDX")?;
+            stream.save(&8u32)?;
+            stream.save(value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Load for CellChanges {
+    fn load(stream: &mut Reader<'_>) -> io::Result<Self> {
+        let mut this: Self = default();
+
+        // `NAME` and `DATA` are only decoded at their usual position, so that saving them first
+        // keeps the original order.
+        let mut index = 0;
+        let mut in_reference = false;
+
+        while let Ok(tag) = stream.load() {
+            match &tag {
+                b"NAME" if index == 0 => {
+                    this.name = Some(stream.load()?);
+                }
+                b"DATA" if index == usize::from(this.name.is_some()) => {
+                    stream.expect(12u32)?;
+                    this.data = Some(stream.load()?);
+                }
+                b"FRMR" => {
+                    stream.expect(4u32)?;
+                    let packed_indices: u32 = stream.load()?;
+                    this.references.push(ReferenceChanges {
+                        mast_index: packed_indices >> 24,
+                        refr_index: packed_indices & 0xFF_FFFF,
+                        ..default()
+                    });
+                    in_reference = true;
+                }
+                // Map notes follow the references.
+                b"MPCD" | b"MPNT" => {
+                    in_reference = false;
+                    stream.unexpected_tag("CELL", tag)?;
+                }
+                _ => match this.references.last_mut() {
+                    Some(reference) if in_reference => {
+                        reference.load_subrecord(stream, tag)?;
+                    }
+                    _ => {
+                        stream.unexpected_tag("CELL", tag)?;
+                    }
+                },
+            }
+            index += 1;
+        }
+
+        Ok(this)
+    }
+}
+
+impl Save for CellChanges {
+    fn save(&self, stream: &mut Writer) -> io::Result<()> {
+        let start = stream.cursor.position();
+        // NAME
+        if let Some(value) = &self.name {
+            stream.save(b"NAME")?;
+            stream.save(value)?;
+        }
+        // DATA
+        if let Some(value) = &self.data {
+            stream.save(b"DATA")?;
+            stream.save(&12u32)?;
+            stream.save(value)?;
+        }
+        // FRMR
+        for reference in &self.references {
+            stream.save(reference)?;
+        }
+        UnknownSubrecord::insert_all(stream, start, &self.unknown_subrecords)
+    }
+}
+
+impl ReferenceChanges {
+    /// Load a subrecord following the `FRMR` subrecord of the reference.
+    fn load_subrecord(&mut self, stream: &mut Reader<'_>, tag: [u8; 4]) -> io::Result<()> {
+        let index =
+            1 + usize::from(self.id.is_some()) + usize::from(self.transform.is_some()) + self.unknown_subrecords.len();
+        let len: u32 = stream.load()?;
+        match &tag {
+            b"NAME" if index == 1 => {
+                self.id = Some(stream.load_string(len as usize)?);
+            }
+            // Some references store `DATA` twice, only the first is decoded.
+            b"DATA" if len == 24 && self.transform.is_none() => {
+                self.transform = Some(stream.load()?);
+            }
+            _ => {
+                self.unknown_subrecords.push(load_unknown(stream, tag, len, index)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Save for ReferenceChanges {
+    fn save(&self, stream: &mut Writer) -> io::Result<()> {
+        let start = stream.cursor.position();
+        // FRMR
+        stream.save(b"FRMR")?;
+        stream.save(&4u32)?;
+        stream.save(&(self.refr_index | (self.mast_index << 24)))?;
+        // NAME
+        if let Some(value) = &self.id {
+            stream.save(b"NAME")?;
+            stream.save(value)?;
+        }
+        // DATA
+        if let Some(value) = &self.transform {
+            stream.save(b"DATA")?;
+            stream.save(&24u32)?;
+            stream.save(value)?;
+        }
+        UnknownSubrecord::insert_all(stream, start, &self.unknown_subrecords)
+    }
+}
+
+/// Load the data of the subrecord `tag` with length `len` as an unknown subrecord at `index`.
+fn load_unknown(stream: &mut Reader<'_>, tag: [u8; 4], len: u32, index: usize) -> io::Result<UnknownSubrecord> {
+    Ok(UnknownSubrecord {
+        index: index
+            .try_into()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "too many subrecords"))?,
+        tag,
+        data: stream.load_bytes(len as usize)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subrecord(tag: [u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut bytes = tag.to_vec();
+        bytes.extend(u32::try_from(data.len()).unwrap().to_le_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    fn fixed<const N: usize>(value: &str) -> [u8; N] {
+        let mut bytes = [0; N];
+        bytes[..value.len()].copy_from_slice(value.as_bytes());
+        bytes
+    }
+
+    fn string(value: &str) -> Vec<u8> {
+        [value.as_bytes(), &[0]].concat()
+    }
+
+    /// Decode the record data `bytes` through a save, and check that it is written back unchanged.
+    fn round_trip<T: SaveRecord + Clone>(bytes: &[u8]) -> T {
+        let mut save = SaveGame::new();
+        save.objects.push(
+            UnknownRecord {
+                tag: *T::TAG,
+                bytes: bytes.to_vec(),
+                ..default()
+            }
+            .into(),
+        );
+        let records = save.records_of_type::<T>().collect::<io::Result<Vec<_>>>().unwrap();
+        save.set_records_of_type(records.clone()).unwrap();
+        let TES3Object::Unknown(record) = &save.objects[0] else {
+            panic!("expected an unknown record");
+        };
+        assert_eq!(record.bytes, bytes);
+        records.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn game_state() {
+        let gmdt = [
+            &fixed::<64>("Balmora")[..],
+            &[10, 20, 30, 0],
+            &0.5f32.to_le_bytes(),
+            &2i32.to_le_bytes(),
+            &3i32.to_le_bytes(),
+            &50i32.to_le_bytes(),
+            &14.0f32.to_le_bytes(),
+            &4i32.to_le_bytes(),
+            &5i32.to_le_bytes(),
+        ]
+        .concat();
+        let bytes = [subrecord(*b"GMDT", &gmdt), subrecord(*b"XXXX", &[1, 2])].concat();
+
+        let state: GameState = round_trip(&bytes);
+        assert_eq!(
+            state,
+            GameState {
+                cell_name: "Balmora".to_string().into(),
+                fog_color: [10, 20, 30, 0],
+                fog_density: 0.5,
+                current_weather: 2,
+                next_weather: 3,
+                weather_transition: 50,
+                next_transition_hour: 14.0,
+                masser_phase: 4,
+                secunda_phase: Some(5),
+                unknown_subrecords: vec![UnknownSubrecord {
+                    index: 1,
+                    tag: *b"XXXX",
+                    data: vec![1, 2],
+                }],
+            }
+        );
+
+        // Older saves omit the phase of Secunda.
+        let state: GameState = round_trip(&subrecord(*b"GMDT", &gmdt[..92]));
+        assert_eq!(state.secunda_phase, None);
+        assert_eq!(state.masser_phase, 4);
+    }
+
+    #[test]
+    fn active_spells() {
+        let spdt = [
+            &1i32.to_le_bytes()[..],
+            &fixed::<32>("bound dagger"),
+            &[0; 16],
+            &fixed::<32>("player"),
+            &fixed::<32>(""),
+            &[7; 44],
+        ]
+        .concat();
+        let bytes = [
+            subrecord(*b"NAME", &3i32.to_le_bytes()),
+            subrecord(*b"SPDT", &spdt),
+            subrecord(*b"TNAM", &string("player")),
+            subrecord(*b"NPDT", &[1; 56]),
+            subrecord(*b"INAM", &[2; 40]),
+            subrecord(*b"NAM0", &[0; 4]),
+            subrecord(*b"XNAM", &[0]),
+            subrecord(*b"NAME", &4i32.to_le_bytes()),
+            subrecord(*b"SPDT", &spdt),
+            subrecord(*b"XNAM", &[0]),
+        ]
+        .concat();
+
+        let active: ActiveSpells = round_trip(&bytes);
+        let [first, second] = &active.spells[..] else {
+            panic!("expected two spells");
+        };
+        assert_eq!(first.index, 3);
+        assert_eq!(first.data.spell_type, 1);
+        assert_eq!(first.data.id.as_str(), "bound dagger");
+        assert_eq!(first.data.caster_id.as_str(), "player");
+        assert_eq!(first.data.unknown2, [0x0707_0707; 11]);
+        assert_eq!(first.target.as_deref(), Some("player"));
+        let tags: Vec<_> = first.unknown_subrecords.iter().map(|s| (s.index, s.tag)).collect();
+        assert_eq!(tags, [(3, *b"NPDT"), (4, *b"INAM"), (5, *b"NAM0"), (6, *b"XNAM")]);
+        assert_eq!(second.index, 4);
+        assert_eq!(second.target, None);
+        assert_eq!(second.unknown_subrecords[0].index, 2);
+        assert!(active.unknown_subrecords.is_empty());
+    }
+
+    #[test]
+    fn global_map() {
+        let pixels: Vec<u8> = (0..12).collect();
+        let maph = [2u32.to_le_bytes(), 1u32.to_le_bytes()].concat();
+        let bytes = [subrecord(*b"MAPH", &maph), subrecord(*b"MAPD", &pixels)].concat();
+
+        let map: GlobalMap = round_trip(&bytes);
+        assert_eq!(map.size, 2);
+        assert_eq!(map.unknown, 1);
+        assert_eq!(map.pixels, pixels);
+    }
+
+    #[test]
+    fn npc_changes() {
+        let bytes = [
+            subrecord(*b"NAME", &string("fargoth")),
+            subrecord(*b"NPDT", &[50, 0, 10, 0, 3, 0, 0, 0]),
+            subrecord(*b"AI_W", &[0; 14]),
+            subrecord(*b"NPCO", &[&2i32.to_le_bytes()[..], &fixed::<32>("iron dagger")].concat()),
+            subrecord(*b"XIDX", &1u32.to_le_bytes()),
+            subrecord(*b"XHLT", &100i32.to_le_bytes()),
+            subrecord(*b"NPCO", &[&(-1i32).to_le_bytes()[..], &fixed::<32>("gold_001")].concat()),
+            subrecord(*b"WIDX", &[0; 8]),
+        ]
+        .concat();
+
+        let npc: NpcChanges = round_trip(&bytes);
+        assert_eq!(npc.id, "fargoth");
+        assert_eq!(
+            npc.data,
+            NpcChangesData {
+                disposition: 50,
+                reputation: 10,
+                index: 3,
+                ..default()
+            }
+        );
+        let [dagger, gold] = &npc.inventory.items[..] else {
+            panic!("expected two stacks");
+        };
+        assert_eq!((dagger.count, dagger.id.as_str()), (2, "iron dagger"));
+        let tags: Vec<_> = dagger.unknown_subrecords.iter().map(|s| (s.index, s.tag)).collect();
+        assert_eq!(tags, [(1, *b"XIDX"), (2, *b"XHLT")]);
+        assert_eq!((gold.count, gold.id.as_str()), (-1, "gold_001"));
+        assert_eq!(npc.inventory.equipped, [(0, 0)]);
+        assert_eq!(npc.unknown_subrecords[0].index, 2);
+    }
+
+    #[test]
+    fn container_changes() {
+        let bytes = [
+            subrecord(*b"NAME", &string("chest_small_01")),
+            subrecord(*b"INDX", &7u32.to_le_bytes()),
+            subrecord(
+                *b"NPCO",
+                &[&1i32.to_le_bytes()[..], &fixed::<32>("misc_soulgem_petty")].concat(),
+            ),
+            subrecord(*b"XSOL", &string("rat")),
+        ]
+        .concat();
+
+        let container: ContainerChanges = round_trip(&bytes);
+        assert_eq!(container.id, "chest_small_01");
+        assert_eq!(container.index, 7);
+        assert_eq!(container.inventory.items[0].id.as_str(), "misc_soulgem_petty");
+        assert_eq!(container.inventory.items[0].unknown_subrecords[0].tag, *b"XSOL");
+        assert!(container.unknown_subrecords.is_empty());
+    }
+
+    #[test]
+    fn creature_changes() {
+        let bytes = [
+            subrecord(*b"NAME", &string("mudcrab")),
+            subrecord(*b"INDX", &2u32.to_le_bytes()),
+            subrecord(*b"XSCL", &1.5f32.to_le_bytes()),
+            subrecord(*b"AI_W", &[0; 14]),
+            subrecord(
+                *b"NPCO",
+                &[&3i32.to_le_bytes()[..], &fixed::<32>("ingred_crab_meat_01")].concat(),
+            ),
+        ]
+        .concat();
+
+        let creature: CreatureChanges = round_trip(&bytes);
+        assert_eq!(creature.id, "mudcrab");
+        assert_eq!(creature.index, 2);
+        assert_eq!(creature.scale, Some(1.5));
+        assert_eq!(creature.inventory.items[0].count, 3);
+        assert_eq!(creature.unknown_subrecords[0].index, 3);
+    }
+
+    #[test]
+    fn cell_changes() {
+        let pack_indices = |mast_index: u32, refr_index: u32| (refr_index | (mast_index << 24)).to_le_bytes();
+        let transform = [1.0f32, 2.0, 3.0, 0.0, 0.0, 1.5].map(f32::to_le_bytes).concat();
+        let bytes = [
+            subrecord(*b"NAME", &string("")),
+            subrecord(
+                *b"DATA",
+                &[&2u32.to_le_bytes()[..], &(-2i32).to_le_bytes(), &(-9i32).to_le_bytes()].concat(),
+            ),
+            subrecord(*b"NAM8", &[0xFF; 32]),
+            subrecord(*b"FRMR", &pack_indices(1, 42)),
+            subrecord(*b"NAME", &string("fargoth")),
+            subrecord(*b"ACDT", &[3; 8]),
+            subrecord(*b"DATA", &transform),
+            subrecord(*b"DELE", &[0; 4]),
+            subrecord(*b"FRMR", &pack_indices(0, 1)),
+            subrecord(*b"NAME", &string("mudcrab")),
+            subrecord(*b"ZNAM", &[1]),
+            subrecord(*b"MPCD", &[0; 12]),
+            subrecord(*b"MPNT", &string("treasure")),
+        ]
+        .concat();
+
+        let cell: CellChanges = round_trip(&bytes);
+        assert_eq!(cell.name.as_deref(), Some(""));
+        assert_eq!(cell.data.map(|data| data.grid), Some((-2, -9)));
+        let indices: Vec<_> = cell.unknown_subrecords.iter().map(|s| (s.index, s.tag)).collect();
+        assert_eq!(indices, [(2, *b"NAM8"), (11, *b"MPCD"), (12, *b"MPNT")]);
+
+        let [fargoth, mudcrab] = &cell.references[..] else {
+            panic!("expected two references");
+        };
+        assert_eq!((fargoth.mast_index, fargoth.refr_index), (1, 42));
+        assert_eq!(fargoth.id.as_deref(), Some("fargoth"));
+        assert_eq!(fargoth.transform, Some(([1.0, 2.0, 3.0], [0.0, 0.0, 1.5])));
+        let tags: Vec<_> = fargoth.unknown_subrecords.iter().map(|s| (s.index, s.tag)).collect();
+        assert_eq!(tags, [(2, *b"ACDT"), (4, *b"DELE")]);
+        assert_eq!((mudcrab.mast_index, mudcrab.refr_index), (0, 1));
+        assert_eq!(mudcrab.transform, None);
+        assert_eq!(mudcrab.unknown_subrecords[0].tag, *b"ZNAM");
+    }
+
+    #[test]
+    fn cell_changes_without_name() {
+        let transform = [0.0f32; 6].map(f32::to_le_bytes).concat();
+        let bytes = [
+            subrecord(*b"FRMR", &7u32.to_le_bytes()),
+            subrecord(*b"NAME", &string("")),
+            subrecord(*b"DATA", &transform),
+            subrecord(*b"FRMR", &8u32.to_le_bytes()),
+            subrecord(*b"DATA", &transform),
+        ]
+        .concat();
+
+        let cell: CellChanges = round_trip(&bytes);
+        assert_eq!(cell.name, None);
+        assert_eq!(cell.data, None);
+        assert!(cell.unknown_subrecords.is_empty());
+
+        let [named, unnamed] = &cell.references[..] else {
+            panic!("expected two references");
+        };
+        assert_eq!(named.id.as_deref(), Some(""));
+        assert!(named.transform.is_some());
+        assert_eq!(unnamed.id, None);
+        assert!(unnamed.transform.is_some());
+        assert!(unnamed.unknown_subrecords.is_empty());
+    }
+}
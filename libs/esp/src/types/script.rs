@@ -10,6 +10,7 @@ pub struct Script {
     pub variables: Vec<u8>,
     pub bytecode: Vec<u8>,
     pub text: String,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -50,7 +51,7 @@ impl Load for Script {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -8,6 +8,7 @@ pub struct Skill {
     pub skill_id: SkillId,
     pub data: SkillData,
     pub description: String,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -43,7 +44,7 @@ impl Load for Skill {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -9,6 +9,7 @@ pub struct SoundGen {
     pub sound_gen_type: SoundGenType,
     pub creature: String,
     pub sound: String,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 impl Load for SoundGen {
@@ -38,7 +39,7 @@ impl Load for SoundGen {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
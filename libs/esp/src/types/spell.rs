@@ -9,6 +9,7 @@ pub struct Spell {
     pub name: String,
     pub effects: Vec<Effect>,
     pub data: SpellData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -47,7 +48,7 @@ impl Load for Spell {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
@@ -7,6 +7,7 @@ pub struct StartScript {
     pub flags: ObjectFlags,
     pub id: String,
     pub script: String,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 impl Load for StartScript {
@@ -29,7 +30,7 @@ impl Load for StartScript {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
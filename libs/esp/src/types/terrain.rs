@@ -0,0 +1,193 @@
+// external imports
+use glam::Vec3;
+
+// internal imports
+use crate::prelude::*;
+
+/// The size of a cell, in world units.
+const CELL_SIZE: f32 = 8192.0;
+
+/// The distance between two vertices, in world units.
+const VERTEX_SPACING: f32 = 128.0;
+
+/// The heights of all exterior landscapes, for querying the ground at any world position.
+///
+/// Heights are interpolated over the same triangles as [`Landscape::calcuate_triangles`], so
+/// queries match the rendered terrain.
+///
+/// ```no_run
+/// use esp::{LoadOrder, Terrain};
+///
+/// let load_order = LoadOrder::from_paths(&["Morrowind.esm", "Tribunal.esm", "Bloodmoon.esm"])?;
+/// let terrain = Terrain::from_load_order(&load_order);
+/// if let Some(height) = terrain.height_at(-11000.0, -71000.0) {
+///     println!("Seyda Neen is at {height} units");
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Terrain {
+    heights: HashMap<(i32, i32), Box<[[f32; 65]; 65]>>,
+}
+
+impl Terrain {
+    /// Build the terrain from the given landscapes, later landscapes replace earlier ones.
+    pub fn new<'a, I>(landscapes: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Landscape>,
+    {
+        let heights = landscapes
+            .into_iter()
+            .filter(|landscape| {
+                landscape
+                    .landscape_flags
+                    .contains(LandscapeFlags::USES_VERTEX_HEIGHTS_AND_NORMALS)
+            })
+            .map(|landscape| (landscape.grid, landscape.decode_vertex_heights()))
+            .collect();
+        Self { heights }
+    }
+
+    /// Build the terrain from the landscapes of a plugin.
+    pub fn from_plugin(plugin: &Plugin) -> Self {
+        Self::new(plugin.objects_of_type::<Landscape>())
+    }
+
+    /// Build the terrain from the winning landscapes of a load order.
+    pub fn from_load_order(load_order: &LoadOrder) -> Self {
+        Self::new(load_order.objects().filter_map(|object| object.try_into().ok()))
+    }
+
+    /// Whether there is a landscape at the given grid.
+    pub fn contains(&self, grid: (i32, i32)) -> bool {
+        self.heights.contains_key(&grid)
+    }
+
+    /// The height of the ground at the given world position, or `None` if there is no landscape.
+    pub fn height_at(&self, x: f32, y: f32) -> Option<f32> {
+        let quad = self.quad_at(x, y)?;
+        Some(quad.height())
+    }
+
+    /// The unit normal of the ground at the given world position, or `None` if there is no
+    /// landscape.
+    ///
+    /// This is the normal of the triangle at that position, it is not smoothed across triangles.
+    pub fn normal_at(&self, x: f32, y: f32) -> Option<Vec3> {
+        let quad = self.quad_at(x, y)?;
+        let (du, dv) = quad.slopes();
+        Some(Vec3::new(-du / VERTEX_SPACING, -dv / VERTEX_SPACING, 1.0).normalize())
+    }
+
+    /// The quad of vertices containing the given world position.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss, clippy::cast_sign_loss)]
+    fn quad_at(&self, x: f32, y: f32) -> Option<Quad> {
+        let grid = ((x / CELL_SIZE).floor() as i32, (y / CELL_SIZE).floor() as i32);
+        let heights = self.heights.get(&grid)?;
+
+        // Local vertex coordinates, from 0 to 64.
+        let lx = (grid.0 as f32).mul_add(-CELL_SIZE, x) / VERTEX_SPACING;
+        let ly = (grid.1 as f32).mul_add(-CELL_SIZE, y) / VERTEX_SPACING;
+
+        // Positions on the far border belong to the last quad.
+        let qx = (lx.floor() as usize).min(63);
+        let qy = (ly.floor() as usize).min(63);
+
+        Some(Quad {
+            a: heights[qy][qx],
+            b: heights[qy + 1][qx],
+            c: heights[qy][qx + 1],
+            d: heights[qy + 1][qx + 1],
+            u: lx - qx as f32,
+            v: ly - qy as f32,
+            // See `Landscape::calcuate_triangles`.
+            flipped: (qx ^ qy) & 1 == 1,
+        })
+    }
+}
+
+/// The heights of the corners of a quad, and a position within it.
+///
+/// The corners are `a` at `(0, 0)`, `b` at `(0, 1)`, `c` at `(1, 0)`, and `d` at `(1, 1)`. The
+/// quad is split into two triangles along `a-d`, or along `b-c` if it is `flipped`.
+struct Quad {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    u: f32,
+    v: f32,
+    flipped: bool,
+}
+
+impl Quad {
+    /// The height change per vertex step along `u` and `v`, on the triangle of the position.
+    fn slopes(&self) -> (f32, f32) {
+        let Self { a, b, c, d, u, v, .. } = *self;
+        if self.flipped {
+            if u + v <= 1.0 {
+                (c - a, b - a)
+            } else {
+                (d - b, d - c)
+            }
+        } else if u >= v {
+            (c - a, d - c)
+        } else {
+            (d - b, b - a)
+        }
+    }
+
+    /// The height at the position.
+    fn height(&self) -> f32 {
+        let (du, dv) = self.slopes();
+        // Both triangles contain the `b` corner (flipped) or the `a` corner (not flipped).
+        if self.flipped {
+            dv.mul_add(self.v - 1.0, du.mul_add(self.u, self.b))
+        } else {
+            dv.mul_add(self.v, du.mul_add(self.u, self.a))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::zeroed_box;
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn height_at() {
+        let mut heights: Box<[[f32; 65]; 65]> = zeroed_box();
+        for (y, row) in heights.iter_mut().enumerate() {
+            for (x, height) in row.iter_mut().enumerate() {
+                *height = ((x * 7 + y * 13) % 32) as f32 * 8.0;
+            }
+        }
+        let mut landscape = Landscape {
+            grid: (-1, 2),
+            ..default()
+        };
+        landscape.encode_vertex_heights(&heights);
+
+        let terrain = Terrain::new([&landscape]);
+        assert!(terrain.contains((-1, 2)));
+        assert!(terrain.height_at(0.0, 0.0).is_none());
+
+        // Points within each triangle lie on the plane of that triangle.
+        let vertices = landscape.calculate_world_vertices();
+        for triangle in landscape.calcuate_triangles() {
+            let [p, q, r] = triangle.map(|i| vertices[usize::from(i)]);
+            let center = (p + q + r) / 3.0;
+            for point in [center, (p + center) / 2.0, (q + center) / 2.0, (r + center) / 2.0] {
+                let height = terrain.height_at(point.x, point.y).unwrap();
+                assert!((height - point.z).abs() < 1e-2, "{point} {height}");
+            }
+            let mut expected = (q - p).cross(r - p).normalize();
+            if expected.z < 0.0 {
+                expected = -expected;
+            }
+            let normal = terrain.normal_at(center.x, center.y).unwrap();
+            assert!(normal.abs_diff_eq(expected, 1e-5), "{normal} {expected}");
+        }
+    }
+}
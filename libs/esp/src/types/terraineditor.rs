@@ -0,0 +1,691 @@
+// rust std imports
+use std::collections::{BTreeMap, BTreeSet};
+
+// external imports
+use bytemuck::zeroed_box;
+use glam::{Vec2, Vec3};
+
+// internal imports
+use crate::prelude::*;
+
+/// The number of vertex steps along each side of a landscape.
+const STEPS: i32 = 64;
+
+/// The distance between two vertices, in world units.
+const VERTEX_SPACING: f32 = 128.0;
+
+/// The number of times a landscape is encoded by [`TerrainEditor::apply`], before mismatched
+/// border vertices are given up on.
+const MAX_ATTEMPTS: u32 = 4;
+
+/// An editor for the landscapes of a plugin, as one continuous grid of vertices.
+///
+/// Vertices are addressed by world vertex coordinates, where the vertex `(x, y)` of the landscape
+/// at `grid` is at `(grid.0 * 64 + x, grid.1 * 64 + y)`. Neighboring landscapes share their border
+/// vertices, and the editor keeps these shared vertices identical. Brush operations take world
+/// coordinates, which are 128 units per vertex.
+///
+/// Edits are made to decoded copies of the landscapes, and written back with
+/// [`TerrainEditor::apply`], which also creates any landscapes that did not exist before.
+///
+/// ```
+/// use esp::{Landscape, Plugin, TerrainEditor};
+/// use glam::Vec2;
+///
+/// let mut plugin = Plugin::new();
+/// let mut editor = TerrainEditor::new(&plugin);
+///
+/// // Raise a hill on the border of four landscapes.
+/// editor.raise(Vec2::ZERO, 1024.0, 512.0);
+/// editor.apply(&mut plugin);
+///
+/// assert_eq!(plugin.objects_of_type::<Landscape>().count(), 4);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TerrainEditor {
+    cells: HashMap<(i32, i32), TerrainCell>,
+}
+
+#[derive(Clone, Debug)]
+struct TerrainCell {
+    heights: Box<[[f32; 65]; 65]>,
+    colors: Box<[[[u8; 3]; 65]; 65]>,
+    /// Whether the heights need to be encoded to the landscape.
+    heights_modified: bool,
+    /// Whether the vertex colors need to be written to the landscape.
+    colors_modified: bool,
+    /// Whether the normals need to be written to the landscape, as the heights of this or a
+    /// neighboring landscape changed.
+    normals_modified: bool,
+}
+
+impl TerrainEditor {
+    /// The height of vertices in newly created landscapes.
+    pub const DEFAULT_HEIGHT: f32 = -2048.0;
+
+    /// The color of vertices in landscapes without vertex colors.
+    pub const DEFAULT_COLOR: [u8; 3] = [255, 255, 255];
+
+    /// Create an editor for the landscapes of the given plugin.
+    ///
+    /// Where the border vertices of neighboring landscapes disagree, the landscape to the north or
+    /// east of the border is used.
+    pub fn new(plugin: &Plugin) -> Self {
+        let mut editor = Self::default();
+
+        for landscape in plugin.objects_of_type::<Landscape>() {
+            let mut colors: Box<[[[u8; 3]; 65]; 65]> = zeroed_box();
+            if landscape.landscape_flags.contains(LandscapeFlags::USES_VERTEX_COLORS) {
+                colors.clone_from(&landscape.vertex_colors.data);
+            } else {
+                colors.as_flattened_mut().fill(Self::DEFAULT_COLOR);
+            }
+            let cell = TerrainCell {
+                heights: landscape.decode_vertex_heights(),
+                colors,
+                heights_modified: false,
+                colors_modified: false,
+                normals_modified: false,
+            };
+            editor.cells.insert(landscape.grid, cell);
+        }
+
+        // Make the shared border vertices consistent.
+        let grids: Vec<_> = editor.cells.keys().copied().collect();
+        for grid in grids {
+            for (vx, vy) in border_vertices(grid) {
+                let (Some(height), Some(color)) = (editor.height(vx, vy), editor.color(vx, vy)) else {
+                    continue;
+                };
+                let (x, y) = local((vx, vy), grid);
+                let cell = editor.cells.get_mut(&grid).unwrap_or_else(|| unreachable!());
+                if cell.heights[y][x].to_bits() != height.to_bits() {
+                    cell.heights[y][x] = height;
+                    cell.heights_modified = true;
+                }
+                if cell.colors[y][x] != color {
+                    cell.colors[y][x] = color;
+                    cell.colors_modified = true;
+                }
+            }
+        }
+
+        editor
+    }
+
+    /// Whether a landscape exists (or will be created) at the given grid.
+    pub fn contains(&self, grid: (i32, i32)) -> bool {
+        self.cells.contains_key(&grid)
+    }
+
+    /// The height of the given world vertex, or `None` if no landscape contains it.
+    pub fn height(&self, vx: i32, vy: i32) -> Option<f32> {
+        let (grid, (x, y)) = self.find(vx, vy)?;
+        Some(self.cells[&grid].heights[y][x])
+    }
+
+    /// The vertex color of the given world vertex, or `None` if no landscape contains it.
+    pub fn color(&self, vx: i32, vy: i32) -> Option<[u8; 3]> {
+        let (grid, (x, y)) = self.find(vx, vy)?;
+        Some(self.cells[&grid].colors[y][x])
+    }
+
+    /// The unit normal of the given world vertex, or `None` if no landscape contains it.
+    ///
+    /// Slopes are estimated from the neighboring vertices, including those of neighboring
+    /// landscapes.
+    pub fn normal(&self, vx: i32, vy: i32) -> Option<Vec3> {
+        let height = self.height(vx, vy)?;
+        let slope = |(ax, ay), (bx, by)| {
+            let (a, b) = (self.height(ax, ay), self.height(bx, by));
+            let (a, b, steps) = match (a, b) {
+                (Some(a), Some(b)) => (a, b, 2.0),
+                (Some(a), None) => (a, height, 1.0),
+                (None, Some(b)) => (height, b, 1.0),
+                (None, None) => return 0.0,
+            };
+            (b - a) / (steps * VERTEX_SPACING)
+        };
+        let dx = slope((vx - 1, vy), (vx + 1, vy));
+        let dy = slope((vx, vy - 1), (vx, vy + 1));
+        Some(Vec3::new(-dx, -dy, 1.0).normalize())
+    }
+
+    /// Set the height of the given world vertex, creating landscapes as needed.
+    pub fn set_height(&mut self, vx: i32, vy: i32, height: f32) {
+        self.modify(vx, vy, true, |cell, (x, y)| cell.set_height(x, y, height));
+        self.invalidate_normals(vx, vy);
+    }
+
+    /// Set the vertex color of the given world vertex, creating landscapes as needed.
+    pub fn set_color(&mut self, vx: i32, vy: i32, color: [u8; 3]) {
+        self.modify(vx, vy, true, |cell, (x, y)| cell.set_color(x, y, color));
+    }
+
+    /// Create an empty landscape at the given grid, unless one already exists.
+    ///
+    /// Vertices shared with existing neighbors are copied from them.
+    pub fn insert(&mut self, grid: (i32, i32)) {
+        if !self.cells.contains_key(&grid) {
+            self.create(grid);
+        }
+    }
+
+    /// Set the height of the given world vertex in the existing landscapes that contain it.
+    pub(crate) fn update_height(&mut self, vx: i32, vy: i32, height: f32) {
+        self.modify(vx, vy, false, |cell, (x, y)| cell.set_height(x, y, height));
+        self.invalidate_normals(vx, vy);
+    }
+
+    /// Set the vertex color of the given world vertex in the existing landscapes that contain it.
+    pub(crate) fn update_color(&mut self, vx: i32, vy: i32, color: [u8; 3]) {
+        self.modify(vx, vy, false, |cell, (x, y)| cell.set_color(x, y, color));
+    }
+
+    /// Raise the vertices within `radius` of `center` by up to `amount`, with linear falloff.
+    ///
+    /// Negative amounts lower the vertices.
+    pub fn raise(&mut self, center: Vec2, radius: f32, amount: f32) {
+        for ((vx, vy), weight) in brush(center, radius) {
+            let height = self.height(vx, vy).unwrap_or(Self::DEFAULT_HEIGHT);
+            self.set_height(vx, vy, amount.mul_add(weight, height));
+        }
+    }
+
+    /// Move the vertices within `radius` of `center` towards `height`, with linear falloff.
+    pub fn flatten(&mut self, center: Vec2, radius: f32, height: f32) {
+        for ((vx, vy), weight) in brush(center, radius) {
+            let current = self.height(vx, vy).unwrap_or(Self::DEFAULT_HEIGHT);
+            self.set_height(vx, vy, (height - current).mul_add(weight, current));
+        }
+    }
+
+    /// Move the vertices within `radius` of `center` towards the average of their neighbors.
+    ///
+    /// The `strength` (from 0 to 1) is scaled by a linear falloff. Only existing landscapes are
+    /// smoothed.
+    pub fn smooth(&mut self, center: Vec2, radius: f32, strength: f32) {
+        let mut smoothed = vec![];
+        for ((vx, vy), weight) in brush(center, radius) {
+            let Some(height) = self.height(vx, vy) else {
+                continue;
+            };
+            let neighbors: Vec<f32> = [(vx - 1, vy), (vx + 1, vy), (vx, vy - 1), (vx, vy + 1)]
+                .into_iter()
+                .filter_map(|(nx, ny)| self.height(nx, ny))
+                .collect();
+            #[allow(clippy::cast_precision_loss)]
+            let average = neighbors.iter().sum::<f32>() / neighbors.len() as f32;
+            smoothed.push(((vx, vy), (average - height).mul_add(weight * strength, height)));
+        }
+        // Apply afterwards, so that the result does not depend on the iteration order.
+        for ((vx, vy), height) in smoothed {
+            self.set_height(vx, vy, height);
+        }
+    }
+
+    /// Blend the vertex colors within `radius` of `center` towards `color`, with linear falloff.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn paint(&mut self, center: Vec2, radius: f32, color: [u8; 3]) {
+        for ((vx, vy), weight) in brush(center, radius) {
+            let current = self.color(vx, vy).unwrap_or(Self::DEFAULT_COLOR);
+            let blended = std::array::from_fn(|i| {
+                let (a, b) = (f32::from(current[i]), f32::from(color[i]));
+                (b - a).mul_add(weight, a).round() as u8
+            });
+            self.set_color(vx, vy, blended);
+        }
+    }
+
+    /// Write all modified landscapes back to the plugin.
+    ///
+    /// Landscapes that do not exist in the plugin are appended to it. Returns the vertices whose
+    /// heights could not be encoded exactly, see [`Landscape::encode_vertex_heights`]. Heights
+    /// are only encoded again where they were edited, and afterwards the editor holds the encoded
+    /// heights, which are also used for the normals.
+    ///
+    /// When a border vertex can not be encoded exactly, the height that was encoded instead is
+    /// also used for the neighboring landscapes, which are then encoded again. Border vertices
+    /// that still differ from a neighbor afterwards are returned as well, with the neighbor's
+    /// height as the requested height.
+    pub fn apply(&mut self, plugin: &mut Plugin) -> Vec<((i32, i32), ClampedVertex)> {
+        let mut positions: HashMap<(i32, i32), usize> = default();
+        for (i, object) in plugin.objects.iter().enumerate() {
+            if let TES3Object::Landscape(landscape) = object {
+                positions.entry(landscape.grid).or_insert(i);
+            }
+        }
+
+        let mut pending: BTreeSet<(i32, i32)> = self
+            .cells
+            .iter()
+            .filter(|(_, cell)| cell.heights_modified)
+            .map(|(grid, _)| *grid)
+            .collect();
+        let mut attempts: HashMap<(i32, i32), u32> = default();
+        let mut clamped: BTreeMap<VertexKey, ClampedVertex> = default();
+
+        while let Some(grid) = pending.pop_first() {
+            let landscape = landscape_mut(plugin, &mut positions, grid);
+            let vertices = landscape.encode_vertex_heights(&self.cells[&grid].heights);
+            *attempts.entry(grid).or_default() += 1;
+
+            for vertex in vertices {
+                if is_border(vertex.x, vertex.y) {
+                    for other in self.share_border_vertex(grid, vertex) {
+                        if attempts.get(&other).copied().unwrap_or_default() < MAX_ATTEMPTS {
+                            pending.insert(other);
+                        }
+                    }
+                }
+                clamped
+                    .entry((grid, (vertex.x, vertex.y)))
+                    .and_modify(|existing| existing.encoded = vertex.encoded)
+                    .or_insert(vertex);
+            }
+
+            // Continue with the heights as they were encoded.
+            let cell = self.cells.get_mut(&grid).unwrap_or_else(|| unreachable!());
+            cell.heights = landscape.decode_vertex_heights();
+            cell.heights_modified = false;
+            cell.normals_modified = true;
+        }
+
+        // The normals depend on the encoded heights of the neighbors, so are written afterwards.
+        let mut grids: Vec<_> = self
+            .cells
+            .iter()
+            .filter(|(_, cell)| cell.normals_modified || cell.colors_modified)
+            .map(|(grid, _)| *grid)
+            .collect();
+        grids.sort_unstable();
+        for grid in grids {
+            let landscape = landscape_mut(plugin, &mut positions, grid);
+            self.write_normals_and_colors(grid, landscape);
+        }
+
+        for (key, vertex) in border_mismatches(plugin, &positions, attempts.keys().copied()) {
+            clamped.entry(key).or_insert(vertex);
+        }
+
+        clamped.into_iter().map(|((grid, _), vertex)| (grid, vertex)).collect()
+    }
+
+    /// Use the encoded height of a clamped border vertex in all landscapes that contain it.
+    ///
+    /// Returns the other landscapes whose height was changed.
+    fn share_border_vertex(&mut self, grid: (i32, i32), vertex: ClampedVertex) -> Vec<(i32, i32)> {
+        let (vx, vy) = world(grid, (vertex.x, vertex.y));
+        let mut changed = vec![];
+        for other in containing((vx, vy)) {
+            let Some(cell) = self.cells.get_mut(&other) else {
+                continue;
+            };
+            let (x, y) = local((vx, vy), other);
+            if cell.heights[y][x].to_bits() != vertex.encoded.to_bits() {
+                cell.heights[y][x] = vertex.encoded;
+                if other != grid {
+                    cell.heights_modified = true;
+                    changed.push(other);
+                }
+            }
+        }
+        changed
+    }
+
+    /// Write the modified normals and vertex colors of the cell at `grid` to its landscape.
+    fn write_normals_and_colors(&mut self, grid: (i32, i32), landscape: &mut Landscape) {
+        // Replace the normals with ones that take neighboring landscapes into account.
+        if self.cells[&grid].normals_modified {
+            for (y, row) in landscape.vertex_normals.data.iter_mut().enumerate() {
+                for (x, normal) in row.iter_mut().enumerate() {
+                    let (vx, vy) = world(grid, (x, y));
+                    let Some(value) = self.normal(vx, vy) else {
+                        continue;
+                    };
+                    #[allow(clippy::cast_possible_truncation)]
+                    let value = (value * 127.0).round().to_array().map(|v| v as i8);
+                    *normal = value;
+                }
+            }
+        }
+
+        let cell = self.cells.get_mut(&grid).unwrap_or_else(|| unreachable!());
+        if cell.colors_modified {
+            let has_colors = cell.colors.as_flattened().iter().any(|&color| color != Self::DEFAULT_COLOR);
+            if has_colors || landscape.landscape_flags.contains(LandscapeFlags::USES_VERTEX_COLORS) {
+                landscape.vertex_colors.data.clone_from(&cell.colors);
+                landscape.landscape_flags.insert(LandscapeFlags::USES_VERTEX_COLORS);
+            }
+        }
+
+        cell.normals_modified = false;
+        cell.colors_modified = false;
+    }
+
+    /// The grid and local coordinates of a landscape containing the given world vertex.
+    ///
+    /// Prefers the landscape where the vertex is not on the north or east border.
+    fn find(&self, vx: i32, vy: i32) -> Option<((i32, i32), (usize, usize))> {
+        containing((vx, vy))
+            .find(|grid| self.cells.contains_key(grid))
+            .map(|grid| (grid, local((vx, vy), grid)))
+    }
+
+    /// Modify the given world vertex in all landscapes that contain it, creating them if `create`.
+    fn modify(&mut self, vx: i32, vy: i32, create: bool, f: impl Fn(&mut TerrainCell, (usize, usize))) {
+        for grid in containing((vx, vy)) {
+            if create {
+                self.insert(grid);
+            }
+            if let Some(cell) = self.cells.get_mut(&grid) {
+                f(cell, local((vx, vy), grid));
+            }
+        }
+    }
+
+    /// Mark the normals of the landscapes around the given world vertex for updating, as the
+    /// normals of neighboring vertices depend on its height.
+    fn invalidate_normals(&mut self, vx: i32, vy: i32) {
+        for (nx, ny) in [(vx - 1, vy), (vx + 1, vy), (vx, vy - 1), (vx, vy + 1)] {
+            for grid in containing((nx, ny)) {
+                if let Some(cell) = self.cells.get_mut(&grid) {
+                    cell.normals_modified = true;
+                }
+            }
+        }
+    }
+
+    /// Create an empty landscape, sharing the border vertices of any existing neighbors.
+    fn create(&mut self, grid: (i32, i32)) {
+        let mut heights: Box<[[f32; 65]; 65]> = zeroed_box();
+        let mut colors: Box<[[[u8; 3]; 65]; 65]> = zeroed_box();
+        heights.as_flattened_mut().fill(Self::DEFAULT_HEIGHT);
+        colors.as_flattened_mut().fill(Self::DEFAULT_COLOR);
+        for (vx, vy) in border_vertices(grid) {
+            let (x, y) = local((vx, vy), grid);
+            if let Some(height) = self.height(vx, vy) {
+                heights[y][x] = height;
+            }
+            if let Some(color) = self.color(vx, vy) {
+                colors[y][x] = color;
+            }
+        }
+        let cell = TerrainCell {
+            heights,
+            colors,
+            heights_modified: true,
+            colors_modified: true,
+            normals_modified: true,
+        };
+        self.cells.insert(grid, cell);
+    }
+}
+
+impl TerrainCell {
+    fn set_height(&mut self, x: usize, y: usize, height: f32) {
+        self.heights[y][x] = height;
+        self.heights_modified = true;
+    }
+
+    fn set_color(&mut self, x: usize, y: usize, color: [u8; 3]) {
+        self.colors[y][x] = color;
+        self.colors_modified = true;
+    }
+}
+
+/// The landscape at `grid`, which is appended to the plugin if it does not exist yet.
+fn landscape_mut<'a>(
+    plugin: &'a mut Plugin,
+    positions: &mut HashMap<(i32, i32), usize>,
+    grid: (i32, i32),
+) -> &'a mut Landscape {
+    let position = *positions.entry(grid).or_insert_with(|| {
+        plugin.objects.push(Landscape { grid, ..default() }.into());
+        plugin.objects.len() - 1
+    });
+    let TES3Object::Landscape(landscape) = &mut plugin.objects[position] else {
+        unreachable!();
+    };
+    landscape
+}
+
+/// A landscape grid and the local coordinates of a vertex within it.
+type VertexKey = ((i32, i32), (usize, usize));
+
+/// Compare the encoded border vertices of the landscapes at `grids` with their neighbors.
+///
+/// Returns the vertices that differ, with the neighbor's height as the requested height.
+fn border_mismatches(
+    plugin: &Plugin,
+    positions: &HashMap<(i32, i32), usize>,
+    grids: impl Iterator<Item = (i32, i32)>,
+) -> Vec<(VertexKey, ClampedVertex)> {
+    let grids: Vec<_> = grids.collect();
+
+    let mut decoded: HashMap<(i32, i32), Box<[[f32; 65]; 65]>> = default();
+    for &(gx, gy) in &grids {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let grid = (gx + dx, gy + dy);
+                let Some(&position) = positions.get(&grid) else {
+                    continue;
+                };
+                if let TES3Object::Landscape(landscape) = &plugin.objects[position] {
+                    decoded.entry(grid).or_insert_with(|| landscape.decode_vertex_heights());
+                }
+            }
+        }
+    }
+
+    let mut mismatches = vec![];
+    for grid in grids {
+        let Some(heights) = decoded.get(&grid) else {
+            continue;
+        };
+        for (vx, vy) in border_vertices(grid) {
+            let (x, y) = local((vx, vy), grid);
+            for other in containing((vx, vy)).filter(|&other| other != grid) {
+                let Some(other_heights) = decoded.get(&other) else {
+                    continue;
+                };
+                let (ox, oy) = local((vx, vy), other);
+                if other_heights[oy][ox].to_bits() != heights[y][x].to_bits() {
+                    let vertex = ClampedVertex {
+                        x,
+                        y,
+                        height: other_heights[oy][ox],
+                        encoded: heights[y][x],
+                    };
+                    mismatches.push(((grid, (x, y)), vertex));
+                }
+            }
+        }
+    }
+    mismatches
+}
+
+/// Whether a local vertex is on the border of its landscape.
+const fn is_border(x: usize, y: usize) -> bool {
+    x == 0 || y == 0 || x == 64 || y == 64
+}
+
+/// The grids of all landscapes that contain the given world vertex.
+///
+/// The first grid is the one where the vertex is not on the north or east border.
+fn containing((vx, vy): (i32, i32)) -> impl Iterator<Item = (i32, i32)> {
+    let (gx, gy) = (vx.div_euclid(STEPS), vy.div_euclid(STEPS));
+    let xs = if vx.rem_euclid(STEPS) == 0 { [gx, gx - 1] } else { [gx, gx] };
+    let ys = if vy.rem_euclid(STEPS) == 0 { [gy, gy - 1] } else { [gy, gy] };
+    let mut grids = vec![];
+    for y in ys {
+        for x in xs {
+            if !grids.contains(&(x, y)) {
+                grids.push((x, y));
+            }
+        }
+    }
+    grids.into_iter()
+}
+
+/// The local coordinates of a world vertex within the landscape at `grid`.
+#[allow(clippy::cast_sign_loss)]
+const fn local((vx, vy): (i32, i32), grid: (i32, i32)) -> (usize, usize) {
+    ((vx - grid.0 * STEPS) as usize, (vy - grid.1 * STEPS) as usize)
+}
+
+/// The world coordinates of a local vertex of the landscape at `grid`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+const fn world(grid: (i32, i32), (x, y): (usize, usize)) -> (i32, i32) {
+    (grid.0 * STEPS + x as i32, grid.1 * STEPS + y as i32)
+}
+
+/// The world coordinates of the border vertices of the landscape at `grid`.
+fn border_vertices(grid: (i32, i32)) -> impl Iterator<Item = (i32, i32)> {
+    (0..65).flat_map(move |i| {
+        [(i, 0), (i, 64), (0, i), (64, i)]
+            .into_iter()
+            .map(move |local| world(grid, local))
+    })
+}
+
+/// The world vertices within `radius` of `center`, with their linear falloff weights.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn brush(center: Vec2, radius: f32) -> Vec<((i32, i32), f32)> {
+    let min = ((center - radius) / VERTEX_SPACING).ceil();
+    let max = ((center + radius) / VERTEX_SPACING).floor();
+    let mut vertices = vec![];
+    for vy in min.y as i32..=max.y as i32 {
+        for vx in min.x as i32..=max.x as i32 {
+            let position = Vec2::new(vx as f32, vy as f32) * VERTEX_SPACING;
+            let distance = position.distance(center);
+            if distance <= radius {
+                let weight = if radius > 0.0 { 1.0 - distance / radius } else { 1.0 };
+                vertices.push(((vx, vy), weight));
+            }
+        }
+    }
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn landscapes(plugin: &Plugin) -> HashMap<(i32, i32), Box<[[f32; 65]; 65]>> {
+        plugin
+            .objects_of_type::<Landscape>()
+            .map(|landscape| (landscape.grid, landscape.decode_vertex_heights()))
+            .collect()
+    }
+
+    #[test]
+    fn shared_borders() {
+        let mut plugin = Plugin::new();
+        let mut editor = TerrainEditor::new(&plugin);
+        editor.raise(Vec2::new(8192.0, 4096.0), 2048.0, 800.0);
+        editor.apply(&mut plugin);
+
+        let heights = landscapes(&plugin);
+        assert_eq!(heights.len(), 2);
+        let west: Vec<f32> = heights[&(0, 0)].iter().map(|row| row[64]).collect();
+        let east: Vec<f32> = heights[&(1, 0)].iter().map(|row| row[0]).collect();
+        assert_eq!(west, east);
+        assert_eq!(west[32..33], [-1248.0]);
+
+        // Edits of an existing landscape's border update its neighbor.
+        let mut editor = TerrainEditor::new(&plugin);
+        editor.set_height(64, 32, -600.0);
+        editor.apply(&mut plugin);
+
+        let heights = landscapes(&plugin);
+        assert_eq!(vec![heights[&(0, 0)][32][64], heights[&(1, 0)][32][0]], [-600.0, -600.0]);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn clamped_borders() {
+        let mut plugin = Plugin::new();
+        let mut editor = TerrainEditor::new(&plugin);
+
+        // A ridge along the border of two landscapes, too steep to encode in the western one.
+        for vy in 1..64 {
+            editor.set_height(64, vy, (vy.min(64 - vy) * 1000 - 2048) as f32);
+        }
+        let clamped = editor.apply(&mut plugin);
+        assert!(!clamped.is_empty());
+        assert!(clamped.iter().all(|(grid, vertex)| *grid == (0, 0) && vertex.x == 64));
+
+        // The eastern landscape uses the heights that the western one could encode.
+        let heights = landscapes(&plugin);
+        let west: Vec<f32> = heights[&(0, 0)].iter().map(|row| row[64]).collect();
+        let east: Vec<f32> = heights[&(1, 0)].iter().map(|row| row[0]).collect();
+        assert_eq!(west, east);
+        assert_eq!(west[32..33], [-2048.0 + 1016.0]);
+        assert_eq!(editor.height(64, 32), Some(-2048.0 + 1016.0));
+    }
+
+    #[test]
+    fn colors_only() {
+        let mut plugin = Plugin::new();
+        let mut editor = TerrainEditor::new(&plugin);
+        editor.raise(Vec2::new(4096.0, 4096.0), 2048.0, 333.3);
+        editor.apply(&mut plugin);
+        let before = plugin.objects_of_type::<Landscape>().next().unwrap().clone();
+
+        // Color edits do not encode the heights again.
+        let mut editor = TerrainEditor::new(&plugin);
+        editor.paint(Vec2::new(4096.0, 4096.0), 512.0, [255, 0, 0]);
+        editor.apply(&mut plugin);
+        let after = plugin.objects_of_type::<Landscape>().next().unwrap();
+        assert_eq!(after.vertex_heights, before.vertex_heights);
+        assert_eq!(after.vertex_normals, before.vertex_normals);
+        assert_eq!(after.world_map_data, before.world_map_data);
+        assert_eq!(after.vertex_colors.data[32][32], [255, 0, 0]);
+    }
+
+    #[test]
+    fn encoded_normals() {
+        let mut plugin = Plugin::new();
+        let mut editor = TerrainEditor::new(&plugin);
+        editor.set_height(32, 32, 100.3);
+        editor.apply(&mut plugin);
+
+        // The editor continues with the encoded heights, and the normals are derived from them.
+        let landscape = plugin.objects_of_type::<Landscape>().next().unwrap();
+        let heights = landscape.decode_vertex_heights();
+        assert_eq!(editor.height(32, 32), Some(heights[32][32]));
+        assert_ne!(heights[32][32].to_bits(), 100.3f32.to_bits());
+
+        let dx = (heights[32][34] - heights[32][32]) / (2.0 * VERTEX_SPACING);
+        let dy = (heights[33][33] - heights[31][33]) / (2.0 * VERTEX_SPACING);
+        #[allow(clippy::cast_possible_truncation)]
+        let expected = (Vec3::new(-dx, -dy, 1.0).normalize() * 127.0)
+            .round()
+            .to_array()
+            .map(|v| v as i8);
+        assert_eq!(landscape.vertex_normals.data[32][33], expected);
+    }
+
+    #[test]
+    fn flatten_smooth_paint() {
+        let mut plugin = Plugin::new();
+        let mut editor = TerrainEditor::new(&plugin);
+        editor.flatten(Vec2::new(4096.0, 4096.0), 256.0, 0.0);
+        assert_eq!(editor.height(32, 32), Some(0.0));
+        assert_eq!(editor.height(33, 32), Some(-1024.0));
+
+        editor.smooth(Vec2::new(4096.0, 4096.0), 0.0, 1.0);
+        assert_eq!(editor.height(32, 32), Some(-1024.0));
+
+        editor.paint(Vec2::new(4096.0, 4096.0), 128.0, [0, 0, 0]);
+        assert_eq!(editor.color(32, 32), Some([0, 0, 0]));
+        assert_eq!(editor.color(33, 32), Some([255, 255, 255]));
+
+        editor.apply(&mut plugin);
+        let landscape = plugin.objects_of_type::<Landscape>().next().unwrap();
+        assert!(landscape.landscape_flags.contains(LandscapeFlags::USES_VERTEX_COLORS));
+        assert_eq!(landscape.vertex_colors.data[32][32], [0, 0, 0]);
+        assert_eq!(editor.normal(0, 0), Some(Vec3::Z));
+    }
+}
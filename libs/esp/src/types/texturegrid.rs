@@ -0,0 +1,432 @@
+// external imports
+use glam::Vec2;
+
+// internal imports
+use crate::prelude::*;
+
+/// The size of a texture within a landscape, in world units.
+const TEXTURE_SIZE: f32 = 512.0;
+
+/// The textures of a landscape by texture id, see [`Plugin::texture_grid`].
+///
+/// The 16x16 textures of a landscape are addressed by column `x` and row `y`, starting from the
+/// south west corner. Texture indices are resolved through the [`LandscapeTexture`] objects of
+/// the same plugin, and the default texture has an empty id.
+#[derive(Debug)]
+pub struct TextureGrid<'a> {
+    landscape: &'a Landscape,
+    textures: TextureIds<'a>,
+}
+
+/// Mutable access to the textures of a landscape by texture id, see [`Plugin::texture_grid_mut`].
+///
+/// Textures are addressed the same way as with [`TextureGrid`].
+///
+/// ```
+/// use esp::{Landscape, LandscapeTexture, Plugin};
+///
+/// let mut plugin = Plugin::new();
+/// plugin.objects.push(LandscapeTexture { id: "Grass".into(), index: 3, ..Default::default() }.into());
+/// plugin.objects.push(Landscape { grid: (0, 0), ..Default::default() }.into());
+///
+/// let mut textures = plugin.texture_grid_mut((0, 0)).unwrap();
+/// textures.set(15, 0, "grass")?;
+/// assert_eq!(textures.get(15, 0), Some("Grass"));
+/// assert_eq!(textures.get(0, 0), Some(""));
+///
+/// let landscape = plugin.objects_of_type::<Landscape>().next().unwrap();
+/// assert_eq!(landscape.texture_indices.data[3][3], 4);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct TextureGridMut<'a> {
+    landscape: &'a mut Landscape,
+    textures: TextureIds<'a>,
+}
+
+/// The ids of the landscape textures of a plugin, sorted by texture index.
+///
+/// A sorted `Vec` rather than a `HashMap`, which would keep the plugin borrowed until the end of
+/// the scope as it implements `Drop`.
+#[derive(Debug)]
+struct TextureIds<'a> {
+    ids: Vec<(u32, &'a str)>,
+}
+
+impl Plugin {
+    /// The textures of the landscape at the given grid.
+    pub fn texture_grid(&self, grid: (i32, i32)) -> Option<TextureGrid<'_>> {
+        let landscape = self.objects_of_type::<Landscape>().find(|landscape| landscape.grid == grid)?;
+        let textures = TextureIds::new(self.objects_of_type());
+        Some(TextureGrid { landscape, textures })
+    }
+
+    /// Mutable access to the textures of the landscape at the given grid.
+    pub fn texture_grid_mut(&mut self, grid: (i32, i32)) -> Option<TextureGridMut<'_>> {
+        // Split the borrow of the objects, so the texture ids can be kept alongside the landscape.
+        let mut landscape = None;
+        let mut textures = vec![];
+        for object in &mut self.objects {
+            match object {
+                TES3Object::Landscape(inner) if landscape.is_none() && inner.grid == grid => landscape = Some(inner),
+                TES3Object::LandscapeTexture(texture) => textures.push(&*texture),
+                _ => {}
+            }
+        }
+        let textures = TextureIds::new(textures);
+        Some(TextureGridMut {
+            landscape: landscape?,
+            textures,
+        })
+    }
+
+    /// Paint a texture onto the landscapes, at every texture whose center is within `radius` of
+    /// the world position `center`.
+    ///
+    /// The texture is matched by `id` (ignoring case). If the plugin has no texture with that id
+    /// yet, a new one is added with the next free index and the given `file_name`, which is then
+    /// required. Landscapes that do not exist are not created. Returns the number of painted
+    /// textures.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss, clippy::cast_sign_loss)]
+    pub fn paint_texture(&mut self, center: Vec2, radius: f32, id: &str, file_name: Option<&str>) -> io::Result<usize> {
+        let value = self.landscape_texture_value(id, file_name)?;
+
+        let positions: HashMap<(i32, i32), usize> = self
+            .objects
+            .iter()
+            .enumerate()
+            .filter_map(|(i, object)| match object {
+                TES3Object::Landscape(landscape) => Some((landscape.grid, i)),
+                _ => None,
+            })
+            .collect();
+
+        let min = ((center - radius) / TEXTURE_SIZE).floor();
+        let max = ((center + radius) / TEXTURE_SIZE).floor();
+
+        let mut count = 0;
+        for ty in min.y as i32..=max.y as i32 {
+            for tx in min.x as i32..=max.x as i32 {
+                let texel_center = (Vec2::new(tx as f32, ty as f32) + 0.5) * TEXTURE_SIZE;
+                if texel_center.distance(center) > radius {
+                    continue;
+                }
+                let grid = (tx.div_euclid(16), ty.div_euclid(16));
+                let Some(&position) = positions.get(&grid) else {
+                    continue;
+                };
+                let TES3Object::Landscape(landscape) = &mut self.objects[position] else {
+                    unreachable!();
+                };
+                let (x, y) = (tx.rem_euclid(16) as usize, ty.rem_euclid(16) as usize);
+                landscape.texture_indices.set(x, y, value);
+                landscape.landscape_flags.insert(LandscapeFlags::USES_TEXTURES);
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// The texture index value of the texture with the given id, adding it to the plugin if needed.
+    fn landscape_texture_value(&mut self, id: &str, file_name: Option<&str>) -> io::Result<u16> {
+        let existing = self
+            .objects_of_type::<LandscapeTexture>()
+            .find(|texture| texture.id.eq_ignore_ascii_case(id))
+            .map(|texture| texture.index);
+
+        if existing.is_none() && file_name.is_none() {
+            let message = format!("Unknown landscape texture: {id}");
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+
+        let index = existing.unwrap_or_else(|| {
+            self.objects_of_type::<LandscapeTexture>()
+                .map(|texture| texture.index + 1)
+                .max()
+                .unwrap_or(0)
+        });
+
+        let value = (index + 1).try_into().map_err(|_| {
+            let message = format!("Landscape texture index out of range: {id}::{index}");
+            io::Error::new(io::ErrorKind::InvalidInput, message)
+        })?;
+
+        if let (None, Some(file_name)) = (existing, file_name) {
+            let texture = LandscapeTexture {
+                id: id.into(),
+                index,
+                file_name: file_name.into(),
+                ..default()
+            };
+            self.objects.push(texture.into());
+        }
+
+        Ok(value)
+    }
+
+    /// Renumber landscape textures from their old index to their new index in `mapping`, and
+    /// rewrite the texture indices of all landscapes to match.
+    ///
+    /// Textures not found in `mapping` keep their index. Fails without making any changes if two
+    /// textures would end up with the same index.
+    pub fn remap_landscape_textures(&mut self, mapping: &HashMap<u32, u32>) -> io::Result<()> {
+        let mut indices: HashSet<u32> = default();
+        for texture in self.objects_of_type::<LandscapeTexture>() {
+            let index = mapping.get(&texture.index).copied().unwrap_or(texture.index);
+            if index >= u32::from(u16::MAX) {
+                let message = format!("Landscape texture index out of range: {}::{index}", texture.id);
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+            }
+            if !indices.insert(index) {
+                let message = format!("Duplicate landscape texture index: {}::{index}", texture.id);
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+            }
+        }
+
+        for texture in self.objects_of_type_mut::<LandscapeTexture>() {
+            if let Some(&index) = mapping.get(&texture.index) {
+                texture.index = index;
+            }
+        }
+        for landscape in self.objects_of_type_mut::<Landscape>() {
+            landscape.texture_indices.remap(mapping);
+        }
+
+        Ok(())
+    }
+
+    /// Remove the landscape textures that are not used by any landscape, and renumber the rest
+    /// to be contiguous from zero, keeping their order.
+    ///
+    /// Returns the removed textures.
+    pub fn prune_landscape_textures(&mut self) -> Vec<LandscapeTexture> {
+        let used: HashSet<u32> = self
+            .objects_of_type::<Landscape>()
+            .filter(|landscape| landscape.landscape_flags.contains(LandscapeFlags::USES_TEXTURES))
+            .flat_map(|landscape| landscape.texture_indices.data.as_flattened())
+            .filter_map(|&value| value.checked_sub(1).map(u32::from))
+            .collect();
+
+        let removed: Vec<LandscapeTexture> = self
+            .objects_of_type::<LandscapeTexture>()
+            .filter(|texture| !used.contains(&texture.index))
+            .cloned()
+            .collect();
+        let unused: HashSet<u32> = removed.iter().map(|texture| texture.index).collect();
+        self.objects.retain(|object| match object {
+            TES3Object::LandscapeTexture(texture) => !unused.contains(&texture.index),
+            _ => true,
+        });
+
+        let mut indices: Vec<u32> = self
+            .objects_of_type::<LandscapeTexture>()
+            .map(|texture| texture.index)
+            .collect();
+        indices.sort_unstable();
+        let mapping = indices.into_iter().zip(0..).collect();
+        self.remap_landscape_textures(&mapping).unwrap_or_else(|_| unreachable!());
+
+        removed
+    }
+}
+
+impl TextureGrid<'_> {
+    pub const fn landscape(&self) -> &Landscape {
+        self.landscape
+    }
+
+    /// The id of the texture at column `x` and row `y`.
+    ///
+    /// The default texture has an empty id. Returns `None` if there is no [`LandscapeTexture`]
+    /// with the stored index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not less than 16.
+    pub fn get(&self, x: usize, y: usize) -> Option<&str> {
+        self.textures.id(self.landscape.texture_indices.get(x, y))
+    }
+}
+
+impl TextureGridMut<'_> {
+    pub fn landscape(&self) -> &Landscape {
+        self.landscape
+    }
+
+    pub fn landscape_mut(&mut self) -> &mut Landscape {
+        self.landscape
+    }
+
+    /// The id of the texture at column `x` and row `y`, see [`TextureGrid::get`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not less than 16.
+    pub fn get(&self, x: usize, y: usize) -> Option<&str> {
+        self.textures.id(self.landscape.texture_indices.get(x, y))
+    }
+
+    /// Set the texture at column `x` and row `y` to the texture with the given id (ignoring case).
+    ///
+    /// An empty id sets the default texture.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not less than 16.
+    pub fn set(&mut self, x: usize, y: usize, id: &str) -> io::Result<()> {
+        let value = self.textures.value(id)?;
+        self.landscape.texture_indices.set(x, y, value);
+        self.landscape.landscape_flags.insert(LandscapeFlags::USES_TEXTURES);
+        Ok(())
+    }
+}
+
+impl<'a> TextureIds<'a> {
+    fn new<I>(textures: I) -> Self
+    where
+        I: IntoIterator<Item = &'a LandscapeTexture>,
+    {
+        let mut ids: Vec<_> = textures.into_iter().map(|texture| (texture.index, &*texture.id)).collect();
+        // Stable sort, so the first texture with a duplicate index is kept.
+        ids.sort_by_key(|&(index, _)| index);
+        ids.dedup_by_key(|&mut (index, _)| index);
+        Self { ids }
+    }
+
+    /// The id of the texture with the raw texture index `value`.
+    fn id(&self, value: u16) -> Option<&'a str> {
+        let Some(index) = value.checked_sub(1) else {
+            return Some("");
+        };
+        let position = self.ids.binary_search_by_key(&u32::from(index), |&(index, _)| index).ok()?;
+        Some(self.ids[position].1)
+    }
+
+    /// The raw texture index of the texture with the given id (ignoring case).
+    fn value(&self, id: &str) -> io::Result<u16> {
+        if id.is_empty() {
+            return Ok(0);
+        }
+        let index = self
+            .ids
+            .iter()
+            .find(|(_, other)| other.eq_ignore_ascii_case(id))
+            .map(|&(index, _)| index)
+            .ok_or_else(|| {
+                let message = format!("Unknown landscape texture: {id}");
+                io::Error::new(io::ErrorKind::InvalidInput, message)
+            })?;
+        (index + 1).try_into().map_err(|_| {
+            let message = format!("Landscape texture index out of range: {id}::{index}");
+            io::Error::new(io::ErrorKind::InvalidInput, message)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_plugin() -> Plugin {
+        let mut plugin = Plugin::new();
+        for (id, index) in [("a", 5), ("b", 2), ("c", 0)] {
+            plugin.objects.push(
+                LandscapeTexture {
+                    id: id.into(),
+                    index,
+                    ..default()
+                }
+                .into(),
+            );
+        }
+        plugin.objects.push(Landscape::default().into());
+        let mut textures = plugin.texture_grid_mut((0, 0)).unwrap();
+        textures.set(0, 0, "A").unwrap();
+        textures.set(5, 9, "b").unwrap();
+        plugin
+    }
+
+    #[test]
+    fn texture_grid() {
+        let mut plugin = test_plugin();
+        let mut textures = plugin.texture_grid_mut((0, 0)).unwrap();
+        assert_eq!(textures.get(0, 0), Some("a"));
+        assert_eq!(textures.get(5, 9), Some("b"));
+        assert_eq!(textures.get(9, 5), Some(""));
+        assert!(textures.set(1, 1, "d").is_err());
+
+        let indices = &textures.landscape().texture_indices;
+        assert_eq!(indices.data[0][0], 6);
+        assert_eq!(indices.data[9][5], 3);
+
+        textures.landscape_mut().texture_indices.set(1, 1, 100);
+        assert_eq!(textures.get(1, 1), None);
+
+        let textures = plugin.texture_grid((0, 0)).unwrap();
+        assert_eq!(textures.get(0, 0), Some("a"));
+        assert_eq!(textures.get(1, 1), None);
+        assert!(plugin.texture_grid((1, 0)).is_none());
+    }
+
+    #[test]
+    fn paint_texture() {
+        let mut plugin = test_plugin();
+        plugin.objects.push(
+            Landscape {
+                grid: (-1, 0),
+                ..default()
+            }
+            .into(),
+        );
+        // An unknown texture needs a file name.
+        assert!(plugin.paint_texture(Vec2::ZERO, 1100.0, "Road", None).is_err());
+
+        // A circle on the corner of the two landscapes, with 2x2 textures in each quarter. The
+        // southern quarters have no landscape.
+        let count = plugin.paint_texture(Vec2::ZERO, 1100.0, "Road", Some("road.dds")).unwrap();
+        assert_eq!(count, 8);
+
+        let texture = plugin.objects_of_type::<LandscapeTexture>().last().unwrap();
+        assert_eq!((texture.id.as_str(), texture.index), ("Road", 6));
+        assert_eq!(texture.file_name, "road.dds");
+
+        let textures = plugin.texture_grid((0, 0)).unwrap();
+        assert_eq!(textures.get(0, 0), Some("Road"));
+        assert_eq!(textures.get(1, 1), Some("Road"));
+        assert_eq!(textures.get(2, 0), Some(""));
+        let textures = plugin.texture_grid((-1, 0)).unwrap();
+        assert_eq!(textures.get(15, 0), Some("Road"));
+        assert!(textures.landscape().landscape_flags.contains(LandscapeFlags::USES_TEXTURES));
+
+        // The existing texture is reused.
+        plugin.paint_texture(Vec2::new(-3000.0, 3000.0), 100.0, "road", None).unwrap();
+        assert_eq!(plugin.objects_of_type::<LandscapeTexture>().count(), 4);
+    }
+
+    #[test]
+    fn remap_landscape_textures() {
+        let mut plugin = test_plugin();
+        plugin.remap_landscape_textures(&[(5, 1), (2, 7)].into()).unwrap();
+        assert!(plugin.remap_landscape_textures(&[(1, 0)].into()).is_err());
+
+        let textures = plugin.texture_grid((0, 0)).unwrap();
+        assert_eq!(textures.get(0, 0), Some("a"));
+        assert_eq!(textures.get(5, 9), Some("b"));
+        assert_eq!(textures.landscape().texture_indices.get(5, 9), 8);
+
+        let removed = plugin.prune_landscape_textures();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].id, "c");
+
+        let indices: Vec<_> = plugin
+            .objects_of_type::<LandscapeTexture>()
+            .map(|texture| (texture.id.as_str(), texture.index))
+            .collect();
+        assert_eq!(indices, [("a", 0), ("b", 1)]);
+
+        let textures = plugin.texture_grid((0, 0)).unwrap();
+        assert_eq!(textures.get(0, 0), Some("a"));
+        assert_eq!(textures.get(5, 9), Some("b"));
+    }
+}
@@ -0,0 +1,347 @@
+// internal imports
+use crate::prelude::*;
+
+/// A dialogue topic and its infos, see [`Plugin::topic`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Topic<'a> {
+    pub dialogue: &'a Dialogue,
+    /// The infos of the topic, in the order they appear in the plugin.
+    pub infos: Vec<&'a DialogueInfo>,
+}
+
+/// Mutable access to a dialogue topic and its infos, see [`Plugin::topic_mut`].
+///
+/// The infos of a topic are the [`DialogueInfo`] objects directly following its [`Dialogue`] in
+/// [`Plugin::objects`], and are also linked together by their `prev_id` and `next_id`. The
+/// operations of this type keep both in sync. Links to infos that are not part of the plugin
+/// (e.g. infos of a master that this plugin inserts between) are preserved.
+///
+/// ```
+/// use esp::{Dialogue, DialogueInfo, Plugin};
+///
+/// let mut plugin = Plugin::new();
+/// plugin.objects.push(Dialogue { id: "Background".into(), ..Default::default() }.into());
+///
+/// let mut topic = plugin.topic_mut("background").unwrap();
+/// topic.push(DialogueInfo { id: "1".into(), ..Default::default() });
+/// topic.insert_after("", DialogueInfo { id: "2".into(), ..Default::default() })?;
+///
+/// let topic = plugin.topic("background").unwrap();
+/// assert_eq!(topic.infos[0].next_id, "1");
+/// assert_eq!(topic.infos[1].prev_id, "2");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct TopicMut<'a> {
+    plugin: &'a mut Plugin,
+    /// The position of the topic's dialogue in the plugin's objects.
+    position: usize,
+}
+
+impl Plugin {
+    /// The dialogue with the given id (ignoring case) and its infos.
+    pub fn topic(&self, id: &str) -> Option<Topic<'_>> {
+        let position = self.topic_position(id)?;
+        let TES3Object::Dialogue(dialogue) = &self.objects[position] else {
+            return None;
+        };
+        let infos = self.objects[position + 1..]
+            .iter()
+            .map_while(|object| object.try_into().ok())
+            .collect();
+        Some(Topic { dialogue, infos })
+    }
+
+    /// Mutable access to the dialogue with the given id (ignoring case) and its infos.
+    pub fn topic_mut(&mut self, id: &str) -> Option<TopicMut<'_>> {
+        let position = self.topic_position(id)?;
+        Some(TopicMut { plugin: self, position })
+    }
+
+    fn topic_position(&self, id: &str) -> Option<usize> {
+        self.objects.iter().position(|object| match object {
+            TES3Object::Dialogue(dialogue) => dialogue.id.eq_ignore_ascii_case(id),
+            _ => false,
+        })
+    }
+}
+
+impl TopicMut<'_> {
+    pub fn dialogue(&self) -> &Dialogue {
+        let TES3Object::Dialogue(dialogue) = &self.plugin.objects[self.position] else {
+            unreachable!();
+        };
+        dialogue
+    }
+
+    pub fn dialogue_mut(&mut self) -> &mut Dialogue {
+        let TES3Object::Dialogue(dialogue) = &mut self.plugin.objects[self.position] else {
+            unreachable!();
+        };
+        dialogue
+    }
+
+    /// The number of infos in the topic.
+    pub fn len(&self) -> usize {
+        self.plugin.objects[self.position + 1..]
+            .iter()
+            .take_while(|object| matches!(object, TES3Object::DialogueInfo(_)))
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The infos of the topic, in order.
+    pub fn infos(&self) -> impl Iterator<Item = &DialogueInfo> {
+        self.plugin.objects[self.position + 1..]
+            .iter()
+            .map_while(|object| object.try_into().ok())
+    }
+
+    /// Mutable access to the infos of the topic, in order.
+    ///
+    /// Changing the `id`, `prev_id`, or `next_id` of the infos may break their links, see
+    /// [`TopicMut::repair`].
+    pub fn infos_mut(&mut self) -> impl Iterator<Item = &mut DialogueInfo> {
+        self.plugin.objects[self.position + 1..]
+            .iter_mut()
+            .map_while(|object| object.try_into().ok())
+    }
+
+    /// The index of the info with the given id within the topic.
+    pub fn index_of(&self, id: &str) -> Option<usize> {
+        self.infos().position(|info| info.id == id)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&DialogueInfo> {
+        self.infos().find(|info| info.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut DialogueInfo> {
+        self.infos_mut().find(|info| info.id == id)
+    }
+
+    /// Insert an info at the end of the topic.
+    pub fn push(&mut self, info: DialogueInfo) {
+        self.attach(self.len(), info);
+    }
+
+    /// Insert an info directly after the info with id `prev_id`.
+    ///
+    /// An empty `prev_id` inserts the info at the start of the topic.
+    pub fn insert_after(&mut self, prev_id: &str, info: DialogueInfo) -> io::Result<()> {
+        let index = if prev_id.is_empty() {
+            0
+        } else {
+            self.expect_index_of(prev_id)? + 1
+        };
+        self.attach(index, info);
+        Ok(())
+    }
+
+    /// Move the info with id `id` directly before the info with id `next_id`.
+    ///
+    /// An empty `next_id` moves the info to the end of the topic. Moving an info before itself
+    /// does nothing.
+    pub fn move_before(&mut self, id: &str, next_id: &str) -> io::Result<()> {
+        let index = self.expect_index_of(id)?;
+        if id == next_id {
+            return Ok(());
+        }
+        // The destination once the info is detached, found first so that no error loses the info.
+        let destination = if next_id.is_empty() {
+            self.len() - 1
+        } else {
+            let next_index = self.expect_index_of(next_id)?;
+            if next_index > index {
+                next_index - 1
+            } else {
+                next_index
+            }
+        };
+        let info = self.detach(index);
+        self.attach(destination, info);
+        Ok(())
+    }
+
+    /// Remove the info with the given id, linking its neighbors together.
+    pub fn remove(&mut self, id: &str) -> Option<DialogueInfo> {
+        let index = self.index_of(id)?;
+        Some(self.detach(index))
+    }
+
+    /// Rebuild the links between the infos from their order in the plugin.
+    ///
+    /// Links at the ends of the topic are kept if they refer to infos that are not part of this
+    /// topic, and cleared otherwise.
+    pub fn repair(&mut self) {
+        let ids: Vec<String> = self.infos().map(|info| info.id.clone()).collect();
+        let is_local = |id: &str| ids.iter().any(|other| other == id);
+        let last = ids.len().saturating_sub(1);
+        for (i, info) in self.infos_mut().enumerate() {
+            if i > 0 {
+                info.prev_id.clone_from(&ids[i - 1]);
+            } else if is_local(&info.prev_id) {
+                info.prev_id.clear();
+            }
+            if i < last {
+                info.next_id.clone_from(&ids[i + 1]);
+            } else if is_local(&info.next_id) {
+                info.next_id.clear();
+            }
+        }
+    }
+
+    fn expect_index_of(&self, id: &str) -> io::Result<usize> {
+        self.index_of(id).ok_or_else(|| {
+            let message = format!("Unknown info id: {}::{id}", self.dialogue().id);
+            io::Error::new(io::ErrorKind::InvalidInput, message)
+        })
+    }
+
+    fn info_at(&mut self, index: usize) -> Option<&mut DialogueInfo> {
+        self.infos_mut().nth(index)
+    }
+
+    /// Insert an info at the given index, linking it to its new neighbors.
+    fn attach(&mut self, index: usize, mut info: DialogueInfo) {
+        let prev = index.checked_sub(1).and_then(|i| self.info_at(i));
+        let has_prev = prev.is_some();
+        if let Some(prev) = prev {
+            info.prev_id.clone_from(&prev.id);
+            info.next_id = std::mem::replace(&mut prev.next_id, info.id.clone());
+        }
+        if let Some(next) = self.info_at(index) {
+            if !has_prev {
+                info.prev_id = std::mem::take(&mut next.prev_id);
+            }
+            info.next_id.clone_from(&next.id);
+            next.prev_id.clone_from(&info.id);
+        }
+        self.plugin.objects.insert(self.position + 1 + index, info.into());
+    }
+
+    /// Remove the info at the given index, linking its neighbors together.
+    fn detach(&mut self, index: usize) -> DialogueInfo {
+        let TES3Object::DialogueInfo(info) = self.plugin.objects.remove(self.position + 1 + index) else {
+            unreachable!();
+        };
+        if let Some(prev) = index.checked_sub(1).and_then(|i| self.info_at(i)) {
+            if prev.next_id == info.id {
+                prev.next_id.clone_from(&info.next_id);
+            }
+        }
+        if let Some(next) = self.info_at(index) {
+            if next.prev_id == info.id {
+                next.prev_id.clone_from(&info.prev_id);
+            }
+        }
+        info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_plugin() -> Plugin {
+        let mut plugin = Plugin::new();
+        plugin.objects.push(
+            Dialogue {
+                id: "Background".into(),
+                ..default()
+            }
+            .into(),
+        );
+        let mut topic = plugin.topic_mut("background").unwrap();
+        for id in ["1", "2", "3"] {
+            topic.push(DialogueInfo {
+                id: id.into(),
+                ..default()
+            });
+        }
+        plugin.objects.push(
+            Dialogue {
+                id: "Other".into(),
+                ..default()
+            }
+            .into(),
+        );
+        plugin
+    }
+
+    fn links(plugin: &Plugin) -> Vec<(&str, &str, &str)> {
+        let topic = plugin.topic("background").unwrap();
+        topic
+            .infos
+            .iter()
+            .map(|info| (info.prev_id.as_str(), info.id.as_str(), info.next_id.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn insert_after() {
+        let mut plugin = test_plugin();
+        assert_eq!(links(&plugin), [("", "1", "2"), ("1", "2", "3"), ("2", "3", "")]);
+
+        let mut topic = plugin.topic_mut("background").unwrap();
+        let info = DialogueInfo {
+            id: "4".into(),
+            ..default()
+        };
+        topic.insert_after("1", info).unwrap();
+        assert!(topic.insert_after("5", DialogueInfo::default()).is_err());
+        assert_eq!(
+            links(&plugin),
+            [("", "1", "4"), ("1", "4", "2"), ("4", "2", "3"), ("2", "3", "")]
+        );
+        assert_eq!(plugin.objects.len(), 6);
+    }
+
+    #[test]
+    fn move_before() {
+        let mut plugin = test_plugin();
+        let mut topic = plugin.topic_mut("background").unwrap();
+        topic.move_before("3", "1").unwrap();
+        assert_eq!(links(&plugin), [("", "3", "1"), ("3", "1", "2"), ("1", "2", "")]);
+
+        let mut topic = plugin.topic_mut("background").unwrap();
+        topic.move_before("1", "").unwrap();
+        assert_eq!(links(&plugin), [("", "3", "2"), ("3", "2", "1"), ("2", "1", "")]);
+
+        // Moving forwards, before itself, or before a missing info.
+        let mut topic = plugin.topic_mut("background").unwrap();
+        topic.move_before("3", "1").unwrap();
+        assert_eq!(links(&plugin), [("", "2", "3"), ("2", "3", "1"), ("3", "1", "")]);
+
+        let mut topic = plugin.topic_mut("background").unwrap();
+        topic.move_before("3", "3").unwrap();
+        assert!(topic.move_before("3", "5").is_err());
+        assert_eq!(links(&plugin), [("", "2", "3"), ("2", "3", "1"), ("3", "1", "")]);
+        assert_eq!(plugin.objects.len(), 5);
+    }
+
+    #[test]
+    fn remove() {
+        let mut plugin = test_plugin();
+        let mut topic = plugin.topic_mut("background").unwrap();
+        assert_eq!(topic.remove("2").unwrap().id, "2");
+        assert!(topic.remove("2").is_none());
+        assert_eq!(links(&plugin), [("", "1", "3"), ("1", "3", "")]);
+        assert_eq!(plugin.objects.len(), 4);
+    }
+
+    #[test]
+    fn repair() {
+        let mut plugin = test_plugin();
+        let mut topic = plugin.topic_mut("background").unwrap();
+        for info in topic.infos_mut() {
+            info.prev_id = "3".into();
+            info.next_id = "master_info".into();
+        }
+        topic.repair();
+        assert_eq!(links(&plugin), [("", "1", "2"), ("1", "2", "3"), ("2", "3", "master_info")]);
+    }
+}
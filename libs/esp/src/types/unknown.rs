@@ -0,0 +1,149 @@
+// rust std imports
+use std::ops::Range;
+
+// internal imports
+use crate::prelude::*;
+
+/// A record of an unknown type, preserved by the lenient loading functions.
+///
+/// The `bytes` field contains the record data following its 16 byte header.
+#[esp_meta]
+#[derive(Clone, Debug, SmartDefault, Eq, PartialEq)]
+pub struct UnknownRecord {
+    pub tag: [u8; 4],
+    pub flags: ObjectFlags,
+    pub bytes: Vec<u8>,
+    /// The encoding of the plugin the record was loaded from, used to decode its editor id.
+    #[default(WINDOWS_1252)]
+    #[cfg_attr(feature = "serde", serde(skip, default = "Plugin::default_encoding"))]
+    #[diff(skip)]
+    pub encoding: &'static Encoding,
+}
+
+/// A subrecord of an unknown type, preserved by the lenient loading functions.
+///
+/// The `index` is the position of the subrecord amongst all subrecords of its parent record, and
+/// is used to insert it back into the same position when saving. The `data` field excludes the
+/// subrecord header.
+///
+/// These are kept in the `unknown_subrecords` field of every record type, so that they travel with
+/// their record through sorting, merging, and other edits. Code constructing records should fill
+/// the remaining fields with `..default()`.
+#[esp_meta]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct UnknownSubrecord {
+    pub index: u32,
+    pub tag: [u8; 4],
+    pub data: Vec<u8>,
+}
+
+impl Save for UnknownRecord {
+    fn save(&self, stream: &mut Writer) -> io::Result<()> {
+        stream.save(&self.flags)?;
+        stream.save_bytes(&self.bytes)
+    }
+}
+
+impl UnknownRecord {
+    pub(crate) fn from_bytes(bytes: &[u8], encoding: &'static Encoding) -> Self {
+        let header = &bytes[..RawRecord::HEADER_SIZE];
+        Self {
+            tag: header[0..4].try_into().unwrap_or_default(),
+            flags: ObjectFlags::from_bits_retain(u32::from_le_bytes(header[12..16].try_into().unwrap_or_default())),
+            bytes: bytes[RawRecord::HEADER_SIZE..].to_vec(),
+            encoding,
+        }
+    }
+}
+
+impl UnknownSubrecord {
+    /// Insert `subrecords` into the record data that was written to `stream` starting at `start`.
+    pub(crate) fn insert_all(stream: &mut Writer, start: u64, subrecords: &[Self]) -> io::Result<()> {
+        if subrecords.is_empty() {
+            return Ok(());
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let data = stream.cursor.get_mut().split_off(start as usize);
+        stream.cursor.set_position(start);
+
+        let mut unknown = subrecords.iter().peekable();
+        let mut index = 0;
+        for range in subrecord_ranges(&data, 0) {
+            while let Some(subrecord) = unknown.next_if(|subrecord| subrecord.index <= index) {
+                stream.save(subrecord)?;
+                index += 1;
+            }
+            stream.save_bytes(&data[range])?;
+            index += 1;
+        }
+        for subrecord in unknown {
+            stream.save(subrecord)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Save for UnknownSubrecord {
+    fn save(&self, stream: &mut Writer) -> io::Result<()> {
+        stream.save(&self.tag)?;
+        stream.save_as::<u32>(self.data.len())?;
+        stream.save_bytes(&self.data)
+    }
+}
+
+impl TES3Object {
+    /// Decode `bytes` as a single object, preserving any unknown records or subrecords.
+    ///
+    /// Known records which still fail to decode are preserved as [`TES3Object::Unknown`].
+    pub(crate) fn load_lenient(bytes: &[u8], encoding: &'static Encoding) -> Self {
+        if !Self::TAGS.iter().any(|tag| bytes.starts_with(*tag)) {
+            return Self::Unknown(UnknownRecord::from_bytes(bytes, encoding));
+        }
+
+        let mut stream = Reader::new(bytes);
+        stream.encoding = encoding;
+        stream.skipped_subrecords = Some(vec![]);
+
+        let Ok(mut object) = stream.load::<Self>() else {
+            return Self::Unknown(UnknownRecord::from_bytes(bytes, encoding));
+        };
+
+        let skipped = stream.skipped_subrecords.unwrap_or_default();
+        if let Some(unknown) = object.unknown_subrecords_mut() {
+            *unknown = UnknownSubrecord::from_skipped(bytes, RawRecord::HEADER_SIZE, &skipped);
+        }
+
+        object
+    }
+}
+
+impl UnknownSubrecord {
+    /// Collect the `skipped` subrecords of the record data in `bytes`, where the subrecords begin
+    /// at `start`.
+    pub(crate) fn from_skipped(bytes: &[u8], start: usize, skipped: &[Range<u64>]) -> Vec<Self> {
+        subrecord_ranges(bytes, start)
+            .enumerate()
+            .filter(|(_, range)| skipped.iter().any(|skipped| skipped.start == range.start as u64))
+            .map(|(index, range)| Self {
+                #[allow(clippy::cast_possible_truncation)]
+                index: index as u32,
+                tag: bytes[range.start..range.start + 4].try_into().unwrap_or_default(),
+                data: bytes[range.start + 8..range.end].to_vec(),
+            })
+            .collect()
+    }
+}
+
+/// Iterate over the byte ranges of the subrecords in `bytes`, including their headers.
+fn subrecord_ranges(bytes: &[u8], start: usize) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut position = start;
+    std::iter::from_fn(move || {
+        let header = bytes.get(position..position + 8)?;
+        let len = u32::from_le_bytes(header[4..8].try_into().ok()?) as usize;
+        let range = position..(position + 8 + len).min(bytes.len());
+        position = range.end;
+        Some(range)
+    })
+}
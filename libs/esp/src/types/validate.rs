@@ -0,0 +1,322 @@
+// rust std imports
+use std::fmt::{self, Display};
+
+// internal imports
+use crate::prelude::*;
+
+/// The longest editor id supported by the engine.
+const MAX_ID_LEN: usize = 32;
+
+/// The longest dialogue text that is fully displayed by the engine.
+const MAX_INFO_TEXT_LEN: usize = 512;
+
+/// The highest index of a dialogue filter.
+const MAX_FILTER_INDEX: u8 = 5;
+
+/// How severe a [`Diagnostic`] is.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    /// The plugin can be saved, but may not behave as intended in game.
+    Warning,
+    /// The plugin cannot be saved, or values will be lost when saving.
+    Error,
+}
+
+/// A single problem found by [`Plugin::validate`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The tag of the record.
+    pub tag: [u8; 4],
+    /// The editor id of the record.
+    pub id: String,
+    /// The path of the field, e.g. `Npc.inventory[0]`.
+    pub path: String,
+    pub message: String,
+}
+
+impl Plugin {
+    /// Check the plugin for problems that would make saving fail or that break engine limits.
+    ///
+    /// Unlike [`Plugin::save_bytes`], which stops at the first problem, this reports every problem
+    /// at once, in the order of the objects. Errors include strings that cannot be encoded or that
+    /// overflow their fixed size, globals with precision errors, out of range reference indices,
+    /// blood types, and filter indices. Warnings include editor ids longer than 32 characters,
+    /// zero inventory counts, and dialogue text longer than 512 characters.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut validator = Validator {
+            encoding: self.encoding,
+            tag: default(),
+            id: default(),
+            diagnostics: vec![],
+        };
+        for object in &self.objects {
+            validator.validate_object(object);
+        }
+        validator.diagnostics
+    }
+}
+
+struct Validator {
+    encoding: &'static Encoding,
+    /// The tag of the record being validated.
+    tag: [u8; 4],
+    /// The editor id of the record being validated.
+    id: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator {
+    fn validate_object(&mut self, object: &TES3Object) {
+        self.tag = *object.tag();
+        self.id = object.editor_id().into_owned();
+
+        let name = object.type_name();
+        match object {
+            TES3Object::Header(header) => {
+                self.fixed_string::<32>(&format!("{name}.author"), &header.author);
+                self.fixed_string::<256>(&format!("{name}.description"), &header.description);
+            }
+            TES3Object::Cell(cell) => self.validate_cell(name, cell),
+            TES3Object::Landscape(_) | TES3Object::PathGrid(_) | TES3Object::Unknown(_) => {}
+            // Info ids are generated numbers, they are never too long.
+            TES3Object::DialogueInfo(info) => self.validate_info(name, info),
+            _ => self.validate_id(name, object),
+        }
+
+        match object {
+            TES3Object::Npc(npc) => {
+                self.validate_actor(name, &npc.inventory, &npc.spells, &npc.ai_packages);
+                self.validate_blood_type(name, npc.blood_type);
+            }
+            TES3Object::Creature(creature) => {
+                self.validate_actor(name, &creature.inventory, &creature.spells, &creature.ai_packages);
+                self.validate_blood_type(name, creature.blood_type);
+            }
+            TES3Object::Container(container) => {
+                self.validate_inventory(name, &container.inventory);
+            }
+            TES3Object::Race(Race { spells, .. }) | TES3Object::Birthsign(Birthsign { spells, .. }) => {
+                self.validate_spells(name, spells);
+            }
+            TES3Object::Faction(faction) => {
+                for (i, rank_name) in faction.rank_names.iter().enumerate() {
+                    self.fixed_string::<32>(&format!("{name}.rank_names[{i}]"), rank_name);
+                }
+            }
+            TES3Object::Region(region) => {
+                for (i, (id, _)) in region.sounds.iter().enumerate() {
+                    self.fixed_string::<32>(&format!("{name}.sounds[{i}]"), id);
+                }
+            }
+            TES3Object::Script(script) => {
+                self.fixed_string::<32>(&format!("{name}.id"), &script.id);
+            }
+            TES3Object::GlobalVariable(global) if global.value.has_precision_error() => {
+                let message = format!("{:?} cannot be stored exactly as a float", global.value);
+                self.push(Severity::Error, &format!("{name}.value"), message);
+            }
+            _ => {}
+        }
+    }
+
+    fn validate_id(&mut self, name: &str, object: &TES3Object) {
+        let id = object.editor_id();
+        let path = format!("{name}.id");
+        if let Some(len) = self.encoded_len(&path, &id) {
+            if len > MAX_ID_LEN {
+                let message = format!("is {len} characters long, the limit is {MAX_ID_LEN}");
+                self.push(Severity::Warning, &path, message);
+            }
+        }
+    }
+
+    fn validate_actor(
+        &mut self,
+        name: &str,
+        inventory: &[(i32, FixedString<32>)],
+        spells: &[String],
+        ai_packages: &[AiPackage],
+    ) {
+        self.validate_inventory(name, inventory);
+        self.validate_spells(name, spells);
+        for (i, package) in ai_packages.iter().enumerate() {
+            let target = match package {
+                AiPackage::Escort(package) => &package.target,
+                AiPackage::Follow(package) => &package.target,
+                AiPackage::Activate(package) => &package.target,
+                _ => continue,
+            };
+            self.fixed_string::<32>(&format!("{name}.ai_packages[{i}].target"), target);
+        }
+    }
+
+    fn validate_inventory(&mut self, name: &str, inventory: &[(i32, FixedString<32>)]) {
+        for (i, (count, id)) in inventory.iter().enumerate() {
+            let path = format!("{name}.inventory[{i}]");
+            self.fixed_string::<32>(&path, id);
+            if *count == 0 {
+                self.push(Severity::Warning, &path, "has a count of zero".into());
+            }
+        }
+    }
+
+    fn validate_spells(&mut self, name: &str, spells: &[String]) {
+        for (i, id) in spells.iter().enumerate() {
+            self.fixed_string::<32>(&format!("{name}.spells[{i}]"), id);
+        }
+    }
+
+    fn validate_blood_type(&mut self, name: &str, blood_type: u8) {
+        if blood_type > 7 {
+            let message = format!("{blood_type} is out of range, the limit is 7");
+            self.push(Severity::Error, &format!("{name}.blood_type"), message);
+        }
+    }
+
+    fn validate_cell(&mut self, name: &str, cell: &Cell) {
+        let references = cell.references.keys().map(|key| ("references", key));
+        let moved_references = cell.moved_references.keys().map(|key| ("moved_references", key));
+        let mut keys: Vec<_> = references.chain(moved_references).collect();
+        keys.sort_unstable();
+        for (field, &(mast_index, refr_index)) in keys {
+            let path = format!("{name}.{field}[{:?}]", (mast_index, refr_index));
+            if mast_index > 0xFF {
+                let message = format!("master index {mast_index} is out of range, the limit is 255");
+                self.push(Severity::Error, &path, message);
+            }
+            if refr_index > 0xFF_FFFF {
+                let message = format!("reference index {refr_index} is out of range, the limit is 16777215");
+                self.push(Severity::Error, &path, message);
+            }
+        }
+    }
+
+    fn validate_info(&mut self, name: &str, info: &DialogueInfo) {
+        let path = format!("{name}.text");
+        if let Some(len) = self.encoded_len(&path, &info.text) {
+            if len > MAX_INFO_TEXT_LEN {
+                let message = format!("is {len} characters long, only {MAX_INFO_TEXT_LEN} are displayed");
+                self.push(Severity::Warning, &path, message);
+            }
+        }
+        for (i, filter) in info.filters.iter().enumerate() {
+            if filter.index > MAX_FILTER_INDEX {
+                let message = format!("index {} is out of range, the limit is {MAX_FILTER_INDEX}", filter.index);
+                self.push(Severity::Error, &format!("{name}.filters[{i}]"), message);
+            }
+        }
+    }
+
+    /// Check that `value` fits in a `FixedString<N>` once encoded.
+    fn fixed_string<const N: usize>(&mut self, path: &str, value: &str) {
+        if let Some(len) = self.encoded_len(path, value) {
+            if len > N {
+                let message = format!("is {len} bytes long, the limit is {N}");
+                self.push(Severity::Error, path, message);
+            }
+        }
+    }
+
+    /// The encoded length of `value`, or `None` if it cannot be encoded.
+    fn encoded_len(&mut self, path: &str, value: &str) -> Option<usize> {
+        let (bytes, _, had_errors) = self.encoding.encode(value);
+        if had_errors {
+            let message = format!("{value:?} cannot be encoded as {}", self.encoding.name());
+            self.push(Severity::Error, path, message);
+            return None;
+        }
+        Some(bytes.len())
+    }
+
+    fn push(&mut self, severity: Severity, path: &str, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            tag: self.tag,
+            id: self.id.clone(),
+            path: path.to_owned(),
+            message,
+        });
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {}: {} {}",
+            self.severity,
+            self.tag.to_str_lossy(),
+            self.id,
+            self.path,
+            self.message
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        let mut cell = Cell {
+            name: "Test Cell".into(),
+            ..default()
+        };
+        cell.references.insert((0, 0x100_0000), default());
+
+        let mut plugin = Plugin {
+            objects: vec![
+                Header::default().into(),
+                Npc {
+                    id: "test_npc".into(),
+                    blood_type: 8,
+                    inventory: vec![(0, "x".repeat(33).into())],
+                    ..default()
+                }
+                .into(),
+                GlobalVariable {
+                    id: "test_global".into(),
+                    value: GlobalValue::Long(16777217),
+                    ..default()
+                }
+                .into(),
+                MiscItem {
+                    id: "x".repeat(33),
+                    ..default()
+                }
+                .into(),
+                cell.into(),
+            ],
+            ..default()
+        };
+
+        let diagnostics: Vec<_> = plugin
+            .validate()
+            .into_iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.path))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                (Severity::Error, "Npc.inventory[0]".into()),
+                (Severity::Warning, "Npc.inventory[0]".into()),
+                (Severity::Error, "Npc.blood_type".into()),
+                (Severity::Error, "GlobalVariable.value".into()),
+                (Severity::Warning, "MiscItem.id".into()),
+                (Severity::Error, "Cell.references[(0, 16777216)]".into()),
+            ]
+        );
+        assert!(plugin.save_bytes().is_err());
+    }
+}
@@ -12,6 +12,7 @@ pub struct Weapon {
     pub icon: String,
     pub enchanting: String,
     pub data: WeaponData,
+    pub unknown_subrecords: Vec<UnknownSubrecord>,
 }
 
 #[esp_meta]
@@ -69,7 +70,7 @@ impl Load for Weapon {
                     this.flags.insert(ObjectFlags::DELETED);
                 }
                 _ => {
-                    Reader::error(format!("Unexpected Tag: {}::{}", this.tag_str(), tag.to_str_lossy()))?;
+                    stream.unexpected_tag(Self::TAG_STR, tag)?;
                 }
             }
         }
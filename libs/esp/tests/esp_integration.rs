@@ -1,6 +1,10 @@
+use glam::Affine3A;
 use tempfile::{NamedTempFile, TempDir};
 
-use esp::Plugin;
+use esp::{
+    Cell, Diff, EditorId, FileType, GameState, Header, Journal, LoadError, LoadOrder, Npc, ObjectFlags, ObjectKey, Plugin,
+    PluginIndex, PluginReader, QuestProgress, SaveGame, SaveGameData, TES3Object, TypeInfo, UnknownRecord,
+};
 
 fn create_temp_file() -> (TempDir, NamedTempFile) {
     let dir = TempDir::new().unwrap();
@@ -44,3 +48,377 @@ fn load_save_json() -> std::io::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn plugin_reader() -> std::io::Result<()> {
+    let src_path = "tests/assets/all_types.esp";
+
+    let plugin = Plugin::from_path(src_path)?;
+
+    let objects: Vec<_> = PluginReader::from_path(src_path)?.collect::<std::io::Result<_>>()?;
+    assert_eq!(plugin.objects, objects);
+
+    let wanted = *Npc::TAG;
+    let mut reader = PluginReader::from_path(src_path)?;
+    let npcs: Vec<_> = reader.objects_filtered(|tag| tag == wanted).collect::<std::io::Result<_>>()?;
+    assert_eq!(plugin.objects_of_type::<Npc>().count(), npcs.len());
+
+    let mut reader = PluginReader::from_path(src_path)?;
+    for (raw, object) in reader.raw_records().zip(&plugin.objects) {
+        let raw = raw?;
+        assert_eq!(&raw.tag, object.tag());
+        assert_eq!(&raw.decode()?, object);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn plugin_index() -> std::io::Result<()> {
+    let src_path = "tests/assets/all_types.esp";
+
+    let plugin = Plugin::from_path(src_path)?;
+    let index = PluginIndex::from_path(src_path)?;
+    assert_eq!(plugin.objects.len(), index.len());
+
+    let npc = plugin.objects_of_type::<Npc>().find(|npc| npc.id == "test_npc");
+    assert_eq!(npc, index.get::<Npc>("TEST_NPC")?.as_ref());
+
+    let cell = plugin.objects_of_type::<Cell>().find(|cell| cell.name == "test_interior");
+    assert_eq!(cell, index.get::<Cell>("Test_Interior")?.as_ref());
+
+    assert!(index.get::<Cell>("test_npc")?.is_none());
+    assert!(index.get::<Npc>("missing")?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn load_error_details() {
+    let src_path = "tests/assets/all_types.esp";
+
+    let mut bytes = std::fs::read(src_path).unwrap();
+
+    // corrupt the size of the first CREA::NPDT subrecord
+    let npdt = bytes.windows(4).position(|window| window == b"NPDT").unwrap();
+    bytes[npdt + 4] = 99;
+
+    let error = Plugin::new().load_bytes(&bytes).unwrap_err();
+    let details = LoadError::from_io_error(&error).unwrap();
+
+    assert_eq!(details.tag_str(), "CREA");
+    assert_eq!(details.subrecord_tag, Some(*b"NPDT"));
+    assert_eq!(details.subrecord_offset, Some(npdt as u64));
+    assert_eq!(details.editor_id.as_deref(), Some("test_creature"));
+    assert_eq!(details.expected.as_deref(), Some("96"));
+    assert_eq!(details.actual.as_deref(), Some("99"));
+    assert!(details.record_offset < npdt as u64);
+
+    // record indices count every record, not only the ones accepted by a filter
+    let filtered_error = Plugin::new().load_bytes_filtered(&bytes, |tag| &tag == b"CREA").unwrap_err();
+    let filtered_details = LoadError::from_io_error(&filtered_error).unwrap();
+    assert_eq!(filtered_details.record_index, details.record_index);
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn subrecord(tag: [u8; 4], data: &[u8]) -> Vec<u8> {
+    [&tag[..], &(data.len() as u32).to_le_bytes(), data].concat()
+}
+
+#[test]
+#[allow(clippy::cast_possible_truncation)]
+fn load_save_lenient() -> std::io::Result<()> {
+    let src_path = "tests/assets/all_types.esp";
+
+    // split the plugin into records
+    let src_bytes = std::fs::read(src_path)?;
+    let mut records = vec![];
+    let mut position = 0;
+    while position < src_bytes.len() {
+        let size = u32::from_le_bytes(src_bytes[position + 4..position + 8].try_into().unwrap()) as usize;
+        records.push(src_bytes[position..position + 16 + size].to_vec());
+        position += 16 + size;
+    }
+
+    // insert an unknown subrecord after the first subrecord of the first npc
+    let npc = records.iter_mut().find(|record| record.starts_with(b"NPC_")).unwrap();
+    let name_size = u32::from_le_bytes(npc[20..24].try_into().unwrap()) as usize;
+    let unknown = subrecord(*b"XNPC", &[1, 2, 3]);
+    npc.splice(24 + name_size..24 + name_size, unknown.iter().copied());
+
+    // insert an unknown subrecord at the end of the first cell
+    let cell = records.iter_mut().find(|record| record.starts_with(b"CELL")).unwrap();
+    cell.extend(subrecord(*b"XCEL", &[4, 5, 6, 7]));
+
+    // insert an unknown record after the header
+    let data = [subrecord(*b"NAME", b"unknown_id\0"), subrecord(*b"DATA", &[8, 9])].concat();
+    let record = [&b"XXXX"[..], &(data.len() as u32).to_le_bytes(), &[0; 8], &data].concat();
+    records.insert(1, record);
+
+    // fix the record sizes and the header object count
+    for record in &mut records {
+        let size = (record.len() - 16) as u32;
+        record[4..8].copy_from_slice(&size.to_le_bytes());
+    }
+    let num_objects = (records.len() - 1) as u32;
+    records[0][320..324].copy_from_slice(&num_objects.to_le_bytes());
+
+    let bytes = records.concat();
+    assert!(Plugin::new().load_bytes(&bytes).is_err());
+
+    let mut plugin = Plugin::new();
+    plugin.load_bytes_lenient(&bytes)?;
+
+    let TES3Object::Unknown(unknown) = &plugin.objects[1] else {
+        panic!("expected an unknown record");
+    };
+    assert_eq!(unknown.tag_str(), "XXXX");
+    assert_eq!(unknown.editor_id(), "unknown_id");
+
+    let npc = plugin.objects_of_type::<Npc>().next().unwrap();
+    assert_eq!(npc.unknown_subrecords.len(), 1);
+    assert_eq!(npc.unknown_subrecords[0].index, 1);
+    assert_eq!(npc.unknown_subrecords[0].tag, *b"XNPC");
+    assert_eq!(npc.unknown_subrecords[0].data, [1, 2, 3]);
+
+    let cell = plugin.objects_of_type::<Cell>().next().unwrap();
+    assert_eq!(cell.unknown_subrecords.len(), 1);
+    assert_eq!(cell.unknown_subrecords[0].tag, *b"XCEL");
+
+    assert_eq!(plugin.save_bytes()?, bytes);
+
+    let reader = PluginReader::from_reader(std::io::Cursor::new(&bytes)).lenient(true);
+    let objects: Vec<_> = reader.collect::<std::io::Result<_>>()?;
+    assert_eq!(plugin.objects, objects);
+
+    Ok(())
+}
+
+#[test]
+fn save_game() -> std::io::Result<()> {
+    let mut save = SaveGame::new();
+    save.objects.push(
+        Header {
+            file_type: FileType::Ess,
+            masters: vec![("Morrowind.esm".into(), 79_837_557)],
+            ..Default::default()
+        }
+        .into(),
+    );
+    let mut player = Npc {
+        id: "player".into(),
+        ..Default::default()
+    };
+    player.data.level = 12;
+    save.objects.push(player.into());
+    save.objects.push(
+        UnknownRecord {
+            tag: *b"GAME",
+            flags: ObjectFlags::default(),
+            bytes: subrecord(*b"GMDT", &[7; 96]),
+            ..Default::default()
+        }
+        .into(),
+    );
+
+    let game_data = SaveGameData {
+        current_health: 50.0,
+        max_health: 100.0,
+        hour: 13.5,
+        cell_name: "Seyda Neen".to_string().into(),
+        player_name: "Nerevar".to_string().into(),
+        ..Default::default()
+    };
+    save.set_game_data(&game_data)?;
+    save.set_screenshot(vec![127; 128 * 128 * 4])?;
+    assert!(save.set_screenshot(vec![0; 16]).is_err());
+
+    let journal = Journal {
+        text: "<FONT COLOR=\"9F0000\">16 Last Seed (Day 1)</FONT>".into(),
+        ..Default::default()
+    };
+    let quest = QuestProgress {
+        id: "A1_1_FindSpymaster".into(),
+        info_ids: vec!["1234567890".into(), "2345678901".into()],
+        ..Default::default()
+    };
+    save.set_records_of_type([journal.clone()])?;
+    save.set_records_of_type([quest.clone()])?;
+
+    let bytes = save.save_bytes()?;
+
+    let mut loaded = SaveGame::new();
+    loaded.load_bytes(&bytes)?;
+    assert_eq!(loaded.game_data()?, Some(game_data));
+    assert_eq!(loaded.screenshot(), save.screenshot());
+    assert_eq!(loaded.player_level(), Some(12));
+    let game_states = loaded.records_of_type::<GameState>().collect::<std::io::Result<Vec<_>>>()?;
+    assert_eq!(game_states[0].secunda_phase, Some(0x0707_0707));
+    assert_eq!(
+        loaded.records_of_type::<Journal>().collect::<std::io::Result<Vec<_>>>()?,
+        [journal]
+    );
+    assert_eq!(
+        loaded
+            .records_of_type::<QuestProgress>()
+            .collect::<std::io::Result<Vec<_>>>()?,
+        [quest]
+    );
+    assert_eq!(loaded.save_bytes()?, bytes);
+
+    Ok(())
+}
+
+#[test]
+fn load_order() -> std::io::Result<()> {
+    let src_path = "tests/assets/all_types.esp";
+
+    let master = Plugin::from_path(src_path)?;
+    let master_size = std::fs::metadata(src_path)?.len();
+
+    let mut npc = master
+        .objects_of_type::<Npc>()
+        .find(|npc| npc.id == "test_npc")
+        .unwrap()
+        .clone();
+    npc.name = "Patched".into();
+
+    let mut cell = master
+        .objects_of_type::<Cell>()
+        .find(|cell| cell.name == "test_interior")
+        .unwrap()
+        .clone();
+    let (&(_, refr_index), reference) = cell.references.iter().next().unwrap();
+    let mut reference = reference.clone();
+    reference.mast_index = 1;
+    reference.scale = Some(2.0);
+    let mut new_reference = reference.clone();
+    new_reference.mast_index = 0;
+    new_reference.refr_index = 1;
+    cell.references.clear();
+    cell.references.insert((1, refr_index), reference);
+    cell.references.insert((0, 1), new_reference);
+
+    let header = Header {
+        masters: vec![("All_Types.esp".into(), master_size)],
+        ..Default::default()
+    };
+    let patch = Plugin {
+        objects: vec![header.into(), npc.into(), cell.into()],
+        ..Default::default()
+    };
+
+    let num_references = master
+        .objects_of_type::<Cell>()
+        .find(|cell| cell.name == "test_interior")
+        .unwrap()
+        .references
+        .len();
+    let num_objects = LoadOrder::from_paths(&[src_path])?.objects().count();
+
+    let load_order = LoadOrder::from_plugins(vec![("all_types.esp".into(), master), ("patch.esp".into(), patch)]);
+    assert_eq!(load_order.objects().count(), num_objects);
+
+    let npc = load_order.get::<Npc>("TEST_NPC").unwrap();
+    assert_eq!(npc.name, "Patched");
+
+    let key = ObjectKey::new(Npc::TAG, "Test_Npc");
+    let names: Vec<_> = load_order.overrides(&key).map(|(name, _)| name).collect();
+    assert_eq!(names, ["all_types.esp", "patch.esp"]);
+
+    let key = ObjectKey::new(Cell::TAG, "test_interior");
+    let references = load_order.cell_references(&key);
+    assert_eq!(references.len(), num_references + 1);
+    assert_eq!(references[&(1, refr_index)].scale, Some(2.0));
+    assert_eq!(references[&(2, 1)].mast_index, 2);
+
+    Ok(())
+}
+
+#[test]
+fn plugin_diff() -> std::io::Result<()> {
+    let old = Plugin::from_path("tests/assets/all_types.esp")?;
+    assert!(esp::diff(&old, &old).is_empty());
+
+    let mut new = old.clone();
+
+    let npc = new.objects_of_type_mut::<Npc>().find(|npc| npc.id == "test_npc").unwrap();
+    npc.data.level += 2;
+    let level = npc.data.level;
+
+    let cell = new
+        .objects_of_type_mut::<Cell>()
+        .find(|cell| cell.name == "test_interior")
+        .unwrap();
+    let (&(mast_index, refr_index), reference) = cell.references.iter_mut().next().unwrap();
+    reference.translation[1] = 123.0;
+
+    let removed = new
+        .objects
+        .iter()
+        .position(|object| object.editor_id() == "test_creature")
+        .unwrap();
+    let removed = new.objects.remove(removed);
+    new.objects.push(
+        Npc {
+            id: "new_npc".into(),
+            ..Default::default()
+        }
+        .into(),
+    );
+
+    let diff = esp::diff(&old, &new);
+    assert_eq!(diff.records.len(), 4);
+    assert_eq!(diff.removed().collect::<Vec<_>>(), [&removed]);
+    assert_eq!(diff.added().map(EditorId::editor_id).collect::<Vec<_>>(), ["new_npc"]);
+
+    let text = diff.to_string();
+    assert!(text.contains(&format!("~ NPC_ test_npc\n    Npc.data.level: {} -> {level}\n", level - 2)));
+    assert!(text.contains(&format!("Cell.references[({mast_index}, {refr_index})].translation[1]: ")));
+    assert!(text.contains("- CREA test_creature\n"));
+    assert!(text.ends_with("+ NPC_ new_npc\n"));
+
+    let mut changes = vec![];
+    old.objects[0].diff(&old.objects[0], &mut String::new(), &mut changes);
+    assert!(changes.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn moved_references() -> std::io::Result<()> {
+    let mut master = Plugin::from_path("tests/assets/all_types.esp")?;
+    let refr_index = master.next_refr_index()?;
+
+    let cell = master
+        .objects_of_type_mut::<Cell>()
+        .find(|cell| cell.name == "test_exterior")
+        .unwrap();
+    cell.add_reference(refr_index, "test_static", Affine3A::IDENTITY)?;
+
+    let mut cell = cell.clone();
+    cell.references.clear();
+    cell.moved_references.insert((1, refr_index), (-14, -8));
+
+    let header = Header {
+        masters: vec![("all_types.esp".into(), 0)],
+        ..Default::default()
+    };
+    let mut patch = Plugin {
+        objects: vec![header.into(), cell.into()],
+        ..Default::default()
+    };
+
+    // Moves of references that are not in the plugin survive a round trip.
+    let bytes = patch.save_bytes()?;
+    let mut loaded = Plugin::new();
+    loaded.load_bytes(&bytes)?;
+    assert_eq!(loaded.objects, patch.objects);
+
+    let key = ObjectKey::exterior_cell((-15, -8));
+    let load_order = LoadOrder::from_plugins(vec![("all_types.esp".into(), master), ("patch.esp".into(), loaded)]);
+    let references = load_order.cell_references(&key);
+    assert_eq!(references[&(1, refr_index)].moved_cell, Some((-14, -8)));
+
+    Ok(())
+}
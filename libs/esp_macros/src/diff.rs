@@ -0,0 +1,93 @@
+use quote::{quote, ToTokens};
+
+/// Implement the `Diff` trait for input.
+///
+/// Structs are compared field by field, and enums variant by variant. Anything else (e.g. the
+/// bitflags wrappers and C-like enums) is compared as a single value. Fields marked with
+/// `#[diff(skip)]` are not compared, the attribute is removed from `input`.
+pub fn impl_diff(input: &mut syn::DeriveInput) -> impl ToTokens {
+    let body = match &mut input.data {
+        syn::Data::Struct(data_struct) => struct_body(&mut data_struct.fields).into_token_stream(),
+        syn::Data::Enum(data_enum) => enum_body(data_enum).into_token_stream(),
+        syn::Data::Union(_) => {
+            return syn::Error::new_spanned(&input.ident, "derive(Diff) does not support unions").to_compile_error();
+        }
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics crate::Diff for #ident #ty_generics #where_clause {
+            fn diff(&self, other: &Self, path: &mut String, changes: &mut Vec<crate::FieldChange>) {
+                #body
+            }
+        }
+    }
+}
+
+/// Remove any `#[diff(skip)]` attributes from `field`, returning whether there were any.
+fn take_skip_attr(field: &mut syn::Field) -> bool {
+    let len = field.attrs.len();
+    field.attrs.retain(|attr| !attr.path().is_ident("diff"));
+    field.attrs.len() != len
+}
+
+fn struct_body(fields: &mut syn::Fields) -> impl ToTokens {
+    match fields {
+        syn::Fields::Named(fields) => {
+            let idents: Vec<_> = fields
+                .named
+                .iter_mut()
+                .filter_map(|field| if take_skip_attr(field) { None } else { field.ident.clone() })
+                .collect();
+            let names = idents.iter().map(|ident| format!(".{ident}"));
+            quote! {
+                #(
+                    let len = path.len();
+                    path.push_str(#names);
+                    crate::Diff::diff(&self.#idents, &other.#idents, path, changes);
+                    path.truncate(len);
+                )*
+            }
+        }
+        // Newtype wrappers are transparent, unless they wrap an associated type (e.g. bitflags).
+        syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 && !is_qualified_path(&fields.unnamed[0].ty) => {
+            quote! {
+                crate::Diff::diff(&self.0, &other.0, path, changes);
+            }
+        }
+        _ => quote! {
+            crate::FieldChange::compare(self, other, path, changes);
+        },
+    }
+}
+
+fn enum_body(data: &syn::DataEnum) -> impl ToTokens {
+    let idents: Vec<_> = data
+        .variants
+        .iter()
+        .filter(|variant| matches!(&variant.fields, syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1))
+        .map(|variant| &variant.ident)
+        .collect();
+
+    if idents.is_empty() {
+        return quote! {
+            crate::FieldChange::compare(self, other, path, changes);
+        };
+    }
+
+    quote! {
+        #[allow(unreachable_patterns)]
+        match (self, other) {
+            #(
+                (Self::#idents(this), Self::#idents(other)) => crate::Diff::diff(this, other, path, changes),
+            )*
+            _ => crate::FieldChange::compare(self, other, path, changes),
+        }
+    }
+}
+
+fn is_qualified_path(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(ty) if ty.qself.is_some())
+}
@@ -67,6 +67,14 @@ fn insert_struct_field_attrs(field: &mut syn::Field) {
         });
     }
 
+    // Skip serializing empty unknown subrecords, these only exist for lenient loading.
+    if ident == "Vec" && is_unknown_subrecords(&segment.arguments) {
+        field.attrs.push(syn::parse_quote! {
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        });
+        return;
+    }
+
     // Otherwise we only care about Box/Vec types.
     if ident != "Box" && ident != "Vec" {
         return;
@@ -96,6 +104,16 @@ fn is_repr_transparent(attributes: &[syn::Attribute]) -> bool {
     false
 }
 
+fn is_unknown_subrecords(arguments: &syn::PathArguments) -> bool {
+    let syn::PathArguments::AngleBracketed(arguments) = arguments else {
+        return false;
+    };
+    arguments.args.iter().any(|arg| match arg {
+        syn::GenericArgument::Type(syn::Type::Path(ty)) => ty.path.is_ident("UnknownSubrecord"),
+        _ => false,
+    })
+}
+
 /// Utility trait for checking if a syntax element is a numeric primitive.
 trait IsNumericPrimitive {
     fn is_numeric_primitive(&self) -> bool;
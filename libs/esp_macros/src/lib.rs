@@ -1,12 +1,12 @@
 use proc_macro::TokenStream;
 use quote::{quote, ToTokens};
 
+mod diff;
 mod features;
 
 #[doc(hidden)]
 #[proc_macro_attribute]
 pub fn esp_meta(_args: TokenStream, input: TokenStream) -> TokenStream {
-    #[allow(unused_mut)]
     let mut input = syn::parse_macro_input!(input as syn::DeriveInput);
 
     #[cfg(feature = "serde")]
@@ -14,8 +14,11 @@ pub fn esp_meta(_args: TokenStream, input: TokenStream) -> TokenStream {
         features::serde::impl_serialize_deserialize(&mut input);
     }
 
+    let impl_diff = diff::impl_diff(&mut input);
+
     let output = quote! {
         #input
+        #impl_diff
     };
 
     output.into()
@@ -31,15 +34,22 @@ pub fn derive_tes3object(input: TokenStream) -> TokenStream {
         _ => panic!("derive(TES3Object) must be on the TES3Object enum"),
     };
 
-    let idents = parse_variant_idents(variants);
-    let tags = parse_variant_tags(variants);
+    // Variants without a `#[tag]` attribute hold records of an unknown type.
+    let (tagged, untagged): (Vec<_>, Vec<_>) = variants.iter().partition(|v| tag_attribute(v).is_some());
+
+    let idents = parse_variant_idents(tagged.iter().copied());
+    let tags = parse_variant_tags(tagged.iter().copied());
+    let untagged_idents = parse_variant_idents(untagged.iter().copied());
+    let untagged_types = parse_variant_types(untagged.iter().copied());
 
     let impl_variants = tes3object_variant_impls(&idents, &tags);
-    let impl_object = tes3object_inherent_impls(&idents);
+    let impl_untagged_variants = tes3object_try_from_impls(&untagged_idents, &untagged_types);
+    let impl_object = tes3object_inherent_impls(&idents, &untagged_idents);
 
     let output = quote! {
         const _: () = {
             #impl_variants
+            #impl_untagged_variants
             #impl_object
         };
     };
@@ -56,6 +66,8 @@ fn tes3object_variant_impls(idents: &[syn::Ident], tags: &[syn::LitStr]) -> impl
         .iter()
         .map(|ident| syn::LitStr::new(&ident.to_string(), ident.span()));
 
+    let impl_try_from = tes3object_try_from_impls(idents, idents);
+
     quote! {
         #(
             #[doc(hidden)]
@@ -64,8 +76,16 @@ fn tes3object_variant_impls(idents: &[syn::Ident], tags: &[syn::LitStr]) -> impl
                 pub const TAG_STR: &'static str = #tags;
                 pub const TYPE_NAME: &'static str = #idents_str;
             }
+        )*
 
-            impl TryFrom<TES3Object> for #idents {
+        #impl_try_from
+    }
+}
+
+fn tes3object_try_from_impls<T: ToTokens>(idents: &[syn::Ident], types: &[T]) -> impl ToTokens {
+    quote! {
+        #(
+            impl TryFrom<TES3Object> for #types {
                 type Error = ();
                 fn try_from(value: TES3Object) -> Result<Self, Self::Error> {
                     match value {
@@ -75,7 +95,7 @@ fn tes3object_variant_impls(idents: &[syn::Ident], tags: &[syn::LitStr]) -> impl
                 }
             }
 
-            impl<'a> TryFrom<&'a TES3Object> for &'a #idents {
+            impl<'a> TryFrom<&'a TES3Object> for &'a #types {
                 type Error = ();
                 fn try_from(value: &'a TES3Object) -> Result<Self, Self::Error> {
                     match value {
@@ -85,7 +105,7 @@ fn tes3object_variant_impls(idents: &[syn::Ident], tags: &[syn::LitStr]) -> impl
                 }
             }
 
-            impl<'a> TryFrom<&'a mut TES3Object> for &'a mut #idents {
+            impl<'a> TryFrom<&'a mut TES3Object> for &'a mut #types {
                 type Error = ();
                 fn try_from(value: &'a mut TES3Object) -> Result<Self, Self::Error> {
                     match value {
@@ -98,10 +118,39 @@ fn tes3object_variant_impls(idents: &[syn::Ident], tags: &[syn::LitStr]) -> impl
     }
 }
 
-fn tes3object_inherent_impls(idents: &[syn::Ident]) -> impl ToTokens {
+fn tes3object_inherent_impls(idents: &[syn::Ident], untagged_idents: &[syn::Ident]) -> impl ToTokens {
     quote! {
         use bytes_io::*;
 
+        impl TES3Object {
+            /// The tags of all known record types.
+            pub const TAGS: &'static [&'static [u8; 4]] = &[#(#idents::TAG),*];
+
+            /// The unknown subrecords that were preserved when loading this object in lenient mode.
+            pub fn unknown_subrecords(&self) -> &[UnknownSubrecord] {
+                match self {
+                    #(
+                        TES3Object::#idents(obj) => &obj.unknown_subrecords,
+                    )*
+                    #(
+                        TES3Object::#untagged_idents(_) => &[],
+                    )*
+                }
+            }
+
+            /// Mutable access to the unknown subrecords, or `None` if this object is of an unknown type.
+            pub fn unknown_subrecords_mut(&mut self) -> Option<&mut Vec<UnknownSubrecord>> {
+                match self {
+                    #(
+                        TES3Object::#idents(obj) => Some(&mut obj.unknown_subrecords),
+                    )*
+                    #(
+                        TES3Object::#untagged_idents(_) => None,
+                    )*
+                }
+            }
+        }
+
         impl Load for TES3Object {
             fn load(stream: &mut Reader<'_>) -> io::Result<Self> {
                 let tag = stream.load()?;
@@ -126,7 +175,14 @@ fn tes3object_inherent_impls(idents: &[syn::Ident]) -> impl ToTokens {
                 // save object & get tag
                 let tag = match self {
                     #(
-                        TES3Object::#idents(obj) => { stream.save(obj)?; obj.tag() }
+                        TES3Object::#idents(obj) => {
+                            stream.save(obj)?;
+                            UnknownSubrecord::insert_all(stream, start_pos + 16, &obj.unknown_subrecords)?;
+                            obj.tag()
+                        }
+                    )*
+                    #(
+                        TES3Object::#untagged_idents(obj) => { stream.save(obj)?; obj.tag() }
                     )*
                 };
 
@@ -150,7 +206,14 @@ fn parse_variant_tags<'a, I>(variants: I) -> Vec<syn::LitStr>
 where
     I: IntoIterator<Item = &'a syn::Variant>,
 {
-    variants.into_iter().map(|v| v.attrs[0].parse_args().unwrap()).collect()
+    variants
+        .into_iter()
+        .map(|v| tag_attribute(v).expect("variant must have a tag").parse_args().unwrap())
+        .collect()
+}
+
+fn tag_attribute(variant: &syn::Variant) -> Option<&syn::Attribute> {
+    variant.attrs.iter().find(|attr| attr.path().is_ident("tag"))
 }
 
 fn parse_variant_idents<'a, I>(variants: I) -> Vec<syn::Ident>
@@ -159,3 +222,13 @@ where
 {
     variants.into_iter().map(|v| v.ident.clone()).collect()
 }
+
+fn parse_variant_types<'a, I>(variants: I) -> Vec<syn::Type>
+where
+    I: IntoIterator<Item = &'a syn::Variant>,
+{
+    variants
+        .into_iter()
+        .map(|v| v.fields.iter().next().expect("variant must have a field").ty.clone())
+        .collect()
+}